@@ -5,13 +5,20 @@ extern crate psutil;
 use std::path::Path;
 use psutil::process::Process;
 
-#[cfg(not(test))]
+#[cfg(all(not(test), target_os = "linux"))]
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let pidfile = Path::new(&args[1][..]);
     let process = Process::from_pidfile(&pidfile).unwrap();
 
-    if let Err(error) = process.kill() {
-        println!("Failed to kill process: {}.", error);
+    match process.kill() {
+        Ok(true) => (),
+        Ok(false) => println!("Process was not running."),
+        Err(error) => println!("Failed to kill process: {}.", error)
     };
 }
+
+#[cfg(all(not(test), not(target_os = "linux")))]
+fn main() {
+    println!("This example only runs on Linux.");
+}
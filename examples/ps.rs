@@ -2,7 +2,7 @@
 
 extern crate psutil;
 
-#[cfg(not(test))]
+#[cfg(all(not(test), target_os = "linux"))]
 fn main() {
     println!("{:>5} {:^5} {:>8} {:>8} {:.100}",
         "PID", "STATE", "UTIME", "STIME", "CMD");
@@ -13,3 +13,8 @@ fn main() {
             p.cmdline().unwrap().unwrap_or(format!("[{}]", p.comm)));
     }
 }
+
+#[cfg(all(not(test), not(target_os = "linux")))]
+fn main() {
+    println!("This example only runs on Linux.");
+}
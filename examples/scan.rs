@@ -0,0 +1,75 @@
+//! Time a scan of every process, and count the allocations it makes
+//!
+//! Run with `cargo run --release --example scan [iterations]`.
+
+extern crate psutil;
+
+use std::alloc::{GlobalAlloc,Layout,System};
+use std::sync::atomic::{AtomicUsize,Ordering};
+use std::time::{Duration,Instant};
+
+/// Counts every allocation, including growing an existing one
+struct Counter;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static COUNTER: Counter = Counter;
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1e6
+}
+
+/// Run `scan` several times, printing the average time and allocations
+fn measure<F: Fn() -> usize>(name: &str, iterations: u32, scan: F) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut count = 0;
+    for _ in 0..iterations {
+        count = scan();
+    }
+    let elapsed = millis(start.elapsed()) / iterations as f64;
+    let allocated = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / iterations as usize;
+
+    println!("{:<20} {:>5} processes in {:>8.3} ms, {:>7} allocations ({:.1} per process)",
+        name, count, elapsed, allocated, allocated as f64 / count.max(1) as f64);
+}
+
+#[cfg(all(not(test), target_os = "linux"))]
+fn main() {
+    let iterations: u32 = std::env::args().nth(1)
+        .map(|arg| arg.parse().expect("iterations must be a number"))
+        .unwrap_or(100);
+
+    measure("Process::new", iterations, || {
+        psutil::process::pids().unwrap().into_iter()
+            .filter_map(|pid| psutil::process::Process::new(pid).ok()).count()
+    });
+    measure("all", iterations, || psutil::process::all().len());
+    measure("all_basic", iterations, || psutil::process::all_basic().unwrap().len());
+    measure("all_basic + smaps", 1, || {
+        psutil::process::all_basic().unwrap().iter()
+            .filter(|&&(ref p, _)| p.memory_full_rollup().is_ok()).count()
+    });
+}
+
+#[cfg(all(not(test), not(target_os = "linux")))]
+fn main() {
+    println!("This example only runs on Linux.");
+}
@@ -2,8 +2,13 @@
 
 extern crate psutil;
 
-#[cfg(not(test))]
+#[cfg(all(not(test), target_os = "linux"))]
 fn main() {
     println!("Processes: {}", psutil::process::all().len());
     println!("System uptime: {} seconds", psutil::system::uptime());
 }
+
+#[cfg(all(not(test), not(target_os = "linux")))]
+fn main() {
+    println!("This example only runs on Linux.");
+}
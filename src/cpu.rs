@@ -0,0 +1,105 @@
+//! Read information about the CPUs from `/proc` and `/sys`
+
+use std::fs::read_dir;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use ::{Error,Result};
+use ::utils::read_file;
+
+/// The clock frequency of a logical CPU
+///
+/// Frequencies are in MHz.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct CpuFrequency {
+    /// Number of the CPU, as in `/sys/devices/system/cpu/cpuN`
+    pub cpu: usize,
+
+    /// Current frequency
+    pub current: f64,
+
+    /// Lowest frequency the CPU can be scaled to, if known
+    pub min: Option<f64>,
+
+    /// Highest frequency the CPU can be scaled to, if known
+    pub max: Option<f64>
+}
+
+/// Read a frequency in kHz from a cpufreq file, as MHz
+fn read_khz(path: &Path) -> Result<f64> {
+    let khz: u64 = try!(try!(read_file(path)).trim().parse().map_err(|_| {
+        Error::new(ErrorKind::Other, format!("Could not parse {}", path.display()))
+    }));
+    Ok(khz as f64 / 1000.0)
+}
+
+/// Parse the `cpu MHz` lines of `/proc/cpuinfo`
+///
+/// Each is matched to the number on the `processor` line before it. These
+/// only give the current frequency, so `min` and `max` are `None`.
+pub fn parse_cpuinfo_mhz(cpuinfo: &str) -> Result<Vec<CpuFrequency>> {
+    let mut frequencies = Vec::new();
+    let mut processor = None;
+
+    for line in cpuinfo.lines() {
+        let mut parts = line.splitn(2, ':');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => continue
+        };
+
+        let invalid = || Error::new(ErrorKind::Other,
+            format!("Could not parse {} from /proc/cpuinfo", key));
+        match key {
+            "processor" => processor = Some(try!(value.parse().map_err(|_| invalid()))),
+            "cpu MHz" => frequencies.push(CpuFrequency {
+                cpu: processor.unwrap_or(frequencies.len()),
+                current: try!(value.parse().map_err(|_| invalid())),
+                min: None,
+                max: None
+            }),
+            _ => ()
+        }
+    }
+
+    return Ok(frequencies);
+}
+
+/// Returns the frequency of each logical CPU, ordered by CPU number
+///
+/// This reads `scaling_cur_freq`, `scaling_min_freq` and `scaling_max_freq`
+/// from `/sys/devices/system/cpu/cpuN/cpufreq`. When cpufreq isn't available,
+/// as in many virtual machines, the current frequency is read from the
+/// `cpu MHz` lines of `/proc/cpuinfo` instead. Systems with neither, such as
+/// most ARM systems without frequency scaling, return an empty vector.
+pub fn frequency() -> Result<Vec<CpuFrequency>> {
+    let mut frequencies = Vec::new();
+
+    for entry in try!(read_dir("/sys/devices/system/cpu")) {
+        let entry = try!(entry);
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let cpu: usize = match name.trim_left_matches("cpu").parse() {
+            Ok(cpu) if name.starts_with("cpu") => cpu,
+            _ => continue
+        };
+
+        let cpufreq = entry.path().join("cpufreq");
+        if !cpufreq.join("scaling_cur_freq").exists() {
+            continue;
+        }
+
+        frequencies.push(CpuFrequency {
+            cpu: cpu,
+            current: try!(read_khz(&cpufreq.join("scaling_cur_freq"))),
+            min: read_khz(&cpufreq.join("scaling_min_freq")).ok(),
+            max: read_khz(&cpufreq.join("scaling_max_freq")).ok()
+        });
+    }
+
+    if frequencies.is_empty() {
+        frequencies = try!(parse_cpuinfo_mhz(&try!(read_file(Path::new("/proc/cpuinfo")))));
+    }
+
+    frequencies.sort_by(|a, b| a.cpu.cmp(&b.cpu));
+    return Ok(frequencies);
+}
@@ -0,0 +1,124 @@
+//! The error type returned by this crate
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use libc::consts::os::posix88::ESRCH;
+
+use ::PID;
+
+/// An error reading or changing a process or the system
+///
+/// Errors about a process as a whole are returned as `NoSuchProcess` or
+/// `AccessDenied`, and everything else as an `Io` error. These all have a
+/// `kind`, which is the same as the `std::io::ErrorKind` they were returned
+/// as before this type existed, so matching on `kind()` works for every
+/// variant. Some `PermissionDenied` errors say which capability is needed,
+/// and are `Io` errors so that they can keep that message.
+#[derive(Debug)]
+pub enum Error {
+    /// The process doesn't exist, or has exited since it was read
+    ///
+    /// Its PID may now belong to another process. This is of the kind
+    /// `NotFound`.
+    NoSuchProcess {
+        /// PID of the process
+        pid: PID
+    },
+
+    /// The calling process isn't permitted to read or change the process
+    ///
+    /// This is of the kind `PermissionDenied`.
+    AccessDenied {
+        /// PID of the process
+        pid: PID
+    },
+
+    /// Any other error, such as a file that could not be parsed
+    Io(io::Error)
+}
+
+impl Error {
+    /// Create an `Io` error, as for `std::io::Error::new`
+    pub fn new<E>(kind: io::ErrorKind, error: E) -> Error
+        where E: Into<Box<dyn error::Error + Send + Sync>> {
+        Error::Io(io::Error::new(kind, error))
+    }
+
+    /// Create an `Io` error from the current value of `errno`
+    pub fn last_os_error() -> Error {
+        Error::Io(io::Error::last_os_error())
+    }
+
+    /// Create an `Io` error from an OS error code, as for
+    /// `std::io::Error::from_raw_os_error`
+    pub fn from_raw_os_error(code: i32) -> Error {
+        Error::Io(io::Error::from_raw_os_error(code))
+    }
+
+    /// Convert an error from reading or changing the process `pid`
+    ///
+    /// `NotFound` and `PermissionDenied` errors become `NoSuchProcess` and
+    /// `AccessDenied`, as do `ESRCH` errors from system calls given the PID,
+    /// and any others are returned unchanged.
+    pub fn for_process<E: Into<Error>>(pid: PID, error: E) -> Error {
+        match error.into() {
+            ref e if e.kind() == io::ErrorKind::NotFound
+                || e.raw_os_error() == Some(ESRCH) => Error::NoSuchProcess { pid: pid },
+            ref e if e.kind() == io::ErrorKind::PermissionDenied => Error::AccessDenied { pid: pid },
+            error => error
+        }
+    }
+
+    /// Return the kind of the error
+    pub fn kind(&self) -> io::ErrorKind {
+        match *self {
+            Error::NoSuchProcess { .. } => io::ErrorKind::NotFound,
+            Error::AccessDenied { .. } => io::ErrorKind::PermissionDenied,
+            Error::Io(ref error) => error.kind()
+        }
+    }
+
+    /// Return the OS error code of an `Io` error, if it has one
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match *self {
+            Error::Io(ref error) => error.raw_os_error(),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NoSuchProcess { pid } => write!(f, "No such process {}", pid),
+            Error::AccessDenied { pid } => write!(f, "Permission denied for process {}", pid),
+            Error::Io(ref error) => error.fmt(f)
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        match error {
+            Error::Io(error) => error,
+            error => io::Error::new(error.kind(), error.to_string())
+        }
+    }
+}
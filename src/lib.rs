@@ -1,12 +1,51 @@
 //! Process monitoring utilities
+//!
+//! ### Platform support
+//!
+//! Everything is supported on Linux, where it is read from `/proc` and `/sys`.
+//! On macOS the `process` module is a smaller one built on `proc_pidinfo`
+//! from `libproc`, and on FreeBSD it is one built on the `kern.proc` sysctls.
+//! The other Linux-only modules are not built on either.
 
 extern crate libc;
 
+#[cfg(target_os = "linux")]
+pub mod cpu;
+mod error;
+#[cfg(target_os = "linux")]
+pub mod memory;
+#[cfg(target_os = "linux")]
+pub mod network;
 pub mod pidfile;
+#[cfg(target_os = "linux")]
 pub mod process;
+#[cfg(target_os = "macos")]
+#[path = "process_macos.rs"]
+pub mod process;
+#[cfg(target_os = "freebsd")]
+#[path = "process_freebsd.rs"]
+pub mod process;
+#[cfg(target_os = "linux")]
 pub mod system;
+#[cfg(target_os = "linux")]
+mod users;
+#[cfg(target_os = "linux")]
 mod utils;
 
+pub use std::io::ErrorKind;
+pub use error::Error;
+pub use process::{Process,State};
+#[cfg(target_os = "linux")]
+pub use process::Signal;
+#[cfg(target_os = "linux")]
+pub use utils::{ReadPolicy,read_policy,retry,set_read_retries};
+
+/// Result type returned by every fallible function in this crate
+///
+/// See `Error` for what can go wrong, and the `process` module for what
+/// `NotFound` and `PermissionDenied` errors mean for a process.
+pub type Result<T> = std::result::Result<T, Error>;
+
 /// Type for process identifiers
 ///
 /// This should expand to `i32` (signed 32 bit integer).
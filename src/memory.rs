@@ -0,0 +1,193 @@
+//! Read information about the system's memory from `/proc` and `/sys`
+
+use std::collections::HashMap;
+use std::fs::read_dir;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use ::{Error,Result};
+use ::utils::read_file;
+
+/// Parse the contents of `/proc/meminfo` into a map of field names to values
+///
+/// Sizes given in kB are converted to bytes, and fields without a unit (such
+/// as `HugePages_Total`, which is a number of pages) are left as they are.
+/// The per-node files in `/sys/devices/system/node` have the same format with
+/// each line prefixed by `Node N`, which is removed from the names. Returns an
+/// error with the kind `Other` if a value is not a number.
+pub fn parse_meminfo(meminfo: &str) -> Result<HashMap<String, u64>> {
+    let mut fields = HashMap::new();
+
+    for line in meminfo.lines() {
+        let mut parts = line.splitn(2, ':');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => continue
+        };
+        let key = if key.starts_with("Node ") {
+            key.splitn(3, ' ').nth(2).unwrap_or(key).trim()
+        } else {
+            key
+        };
+
+        let (number, multiplier) = if value.ends_with(" kB") {
+            (value.trim_right_matches("kB").trim(), 1024)
+        } else {
+            (value, 1)
+        };
+
+        let number: u64 = try!(number.parse().map_err(|_| {
+            Error::new(ErrorKind::Other, format!("Could not parse {} from meminfo", key))
+        }));
+        fields.insert(key.to_string(), number * multiplier);
+    }
+
+    return Ok(fields);
+}
+
+/// When the kernel backs anonymous memory with transparent huge pages
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum ThpMode {
+    /// For all suitable mappings
+    Always,
+
+    /// Only for mappings advised with `madvise(MADV_HUGEPAGE)`
+    Madvise,
+
+    /// Never
+    Never
+}
+
+/// Parse the contents of `/sys/kernel/mm/transparent_hugepage/enabled`
+///
+/// The file lists every mode with the current one in brackets, e.g.
+/// `always [madvise] never`.
+pub fn parse_thp_enabled(enabled: &str) -> Result<ThpMode> {
+    let current = enabled.split_whitespace()
+        .find(|mode| mode.starts_with('[') && mode.ends_with(']'))
+        .map(|mode| mode.trim_matches(|c| c == '[' || c == ']'));
+
+    match current {
+        Some("always") => Ok(ThpMode::Always),
+        Some("madvise") => Ok(ThpMode::Madvise),
+        Some("never") => Ok(ThpMode::Never),
+        _ => Err(Error::new(ErrorKind::Other,
+            "Could not parse /sys/kernel/mm/transparent_hugepage/enabled"))
+    }
+}
+
+/// Huge page usage of the system
+///
+/// Read from the huge page lines of `/proc/meminfo`. The `HugePages_` fields
+/// count persistent huge pages, which are reserved up front for `hugetlbfs`
+/// and `MAP_HUGETLB`, while transparent huge pages are allocated as needed.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct HugePages {
+    /// Size of the pool of persistent huge pages (pages)
+    pub total: u64,
+
+    /// Persistent huge pages that are not allocated (pages)
+    pub free: u64,
+
+    /// Persistent huge pages that are reserved for mappings but not yet
+    /// allocated (pages)
+    pub reserved: u64,
+
+    /// Persistent huge pages allocated above `total`, up to the limit set by
+    /// `vm.nr_overcommit_hugepages` (pages)
+    pub surplus: u64,
+
+    /// Size of a persistent huge page (bytes)
+    pub page_size: u64,
+
+    /// Anonymous memory backed by transparent huge pages (bytes)
+    pub anon_huge_pages: u64,
+
+    /// Shared memory and tmpfs backed by transparent huge pages (bytes)
+    pub shmem_huge_pages: u64,
+
+    /// When transparent huge pages are used, or `None` for a kernel built
+    /// without them
+    pub thp_enabled: Option<ThpMode>
+}
+
+/// Returns the huge page usage of the system
+///
+/// Fields missing from `/proc/meminfo`, such as all of them on kernels built
+/// without huge page support, are zero.
+pub fn hugepages() -> Result<HugePages> {
+    let meminfo = try!(parse_meminfo(&try!(read_file(Path::new("/proc/meminfo")))));
+    let field = |name: &str| meminfo.get(name).cloned().unwrap_or(0);
+
+    let thp_enabled = match read_file(Path::new("/sys/kernel/mm/transparent_hugepage/enabled")) {
+        Ok(enabled) => Some(try!(parse_thp_enabled(&enabled))),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e)
+    };
+
+    return Ok(HugePages {
+        total: field("HugePages_Total"),
+        free: field("HugePages_Free"),
+        reserved: field("HugePages_Rsvd"),
+        surplus: field("HugePages_Surp"),
+        page_size: field("Hugepagesize"),
+        anon_huge_pages: field("AnonHugePages"),
+        shmem_huge_pages: field("ShmemHugePages"),
+        thp_enabled: thp_enabled
+    });
+}
+
+/// Memory usage of a NUMA node
+///
+/// Read from `/sys/devices/system/node/nodeN/meminfo`. All sizes are in bytes.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct NumaNode {
+    /// Number of the node, as in `/sys/devices/system/node/nodeN`
+    pub node: usize,
+
+    /// Memory attached to the node
+    pub total: u64,
+
+    /// Memory of the node that is not used
+    pub free: u64,
+
+    /// Memory of the node that is used, including caches
+    pub used: u64
+}
+
+/// Returns the memory usage of each NUMA node, ordered by node number
+///
+/// Systems that aren't NUMA have a single node. Kernels built without NUMA
+/// support have no nodes in `/sys`, and return an empty vector.
+pub fn numa_nodes() -> Result<Vec<NumaNode>> {
+    let mut nodes = Vec::new();
+
+    let entries = match read_dir("/sys/devices/system/node") {
+        Ok(entries) => entries,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(nodes),
+        Err(e) => return Err(Error::from(e))
+    };
+
+    for entry in entries {
+        let entry = try!(entry);
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let node: usize = match name.trim_left_matches("node").parse() {
+            Ok(node) if name.starts_with("node") => node,
+            _ => continue
+        };
+
+        let meminfo = try!(parse_meminfo(&try!(read_file(&entry.path().join("meminfo")))));
+        let field = |name: &str| meminfo.get(name).cloned().unwrap_or(0);
+        let (total, free) = (field("MemTotal"), field("MemFree"));
+
+        nodes.push(NumaNode {
+            node: node,
+            total: total,
+            free: free,
+            used: meminfo.get("MemUsed").cloned().unwrap_or(total.saturating_sub(free))
+        });
+    }
+
+    nodes.sort_by(|a, b| a.node.cmp(&b.node));
+    return Ok(nodes);
+}
@@ -0,0 +1,380 @@
+//! Read network information from `/proc`
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr,Ipv4Addr,Ipv6Addr,SocketAddr};
+use std::path::Path;
+
+use ::{Error,Result,PID,UID};
+use ::process::{pids,Process};
+use ::utils::read_file;
+
+/// Traffic counters for a network interface
+///
+/// Read from a line of `/proc/net/dev`. The counters are totals since the
+/// interface was created.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct NetIoCounters {
+    /// Bytes received
+    pub bytes_recv: u64,
+
+    /// Packets received
+    pub packets_recv: u64,
+
+    /// Errors while receiving
+    pub errin: u64,
+
+    /// Incoming packets that were dropped
+    pub dropin: u64,
+
+    /// Bytes sent
+    pub bytes_sent: u64,
+
+    /// Packets sent
+    pub packets_sent: u64,
+
+    /// Errors while sending
+    pub errout: u64,
+
+    /// Outgoing packets that were dropped
+    pub dropout: u64
+}
+
+/// Parse the contents of `/proc/net/dev` into counters for each interface
+///
+/// The first two lines are headers. Each interface is on a line of its own,
+/// with its name followed by 8 receive and then 8 transmit counters.
+pub fn parse_net_dev(contents: &str) -> Result<HashMap<String, NetIoCounters>> {
+    let mut interfaces = HashMap::new();
+
+    for line in contents.lines().skip(2) {
+        let mut parts = line.splitn(2, ':');
+        let (name, counters) = match (parts.next(), parts.next()) {
+            (Some(name), Some(counters)) => (name.trim(), counters),
+            _ => return Err(Error::new(ErrorKind::Other,
+                "Could not find interface name in /proc/net/dev"))
+        };
+
+        let mut fields = Vec::with_capacity(16);
+        for field in counters.split_whitespace() {
+            fields.push(try!(field.parse::<u64>().map_err(|_| {
+                Error::new(ErrorKind::Other, "Could not parse counter in /proc/net/dev")
+            })));
+        }
+
+        if fields.len() < 16 {
+            return Err(Error::new(ErrorKind::Other,
+                format!("Expected 16 counters for {} in /proc/net/dev", name)));
+        }
+
+        interfaces.insert(name.to_string(), NetIoCounters {
+            bytes_recv:     fields[0],
+            packets_recv:   fields[1],
+            errin:          fields[2],
+            dropin:         fields[3],
+            bytes_sent:     fields[8],
+            packets_sent:   fields[9],
+            errout:         fields[10],
+            dropout:        fields[11]
+        });
+    }
+
+    return Ok(interfaces);
+}
+
+/// Returns traffic counters for each network interface
+///
+/// These are the interfaces of the network namespace of the calling process.
+/// See `Process::net_io_counters` for those of another namespace.
+pub fn net_io_counters() -> Result<HashMap<String, NetIoCounters>> {
+    parse_net_dev(&try!(read_file(&Path::new("/proc/net/dev"))))
+}
+
+/// Transport protocol of a socket
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum Protocol {
+    Tcp,
+    Udp
+}
+
+/// Kinds of sockets to read, by protocol and address family
+///
+/// `Inet`, `Tcp` and `Udp` include both IPv4 and IPv6 sockets.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum ConnKind {
+    Inet,
+    Inet4,
+    Inet6,
+    Tcp,
+    Tcp4,
+    Tcp6,
+    Udp,
+    Udp4,
+    Udp6
+}
+
+impl ConnKind {
+    /// Return the names of the files in `/proc/net` that list sockets of
+    /// this kind, with the protocol of each
+    pub fn tables(&self) -> &'static [(&'static str, Protocol)] {
+        const TCP4: (&'static str, Protocol) = ("tcp", Protocol::Tcp);
+        const TCP6: (&'static str, Protocol) = ("tcp6", Protocol::Tcp);
+        const UDP4: (&'static str, Protocol) = ("udp", Protocol::Udp);
+        const UDP6: (&'static str, Protocol) = ("udp6", Protocol::Udp);
+
+        match *self {
+            ConnKind::Inet  => &[TCP4, TCP6, UDP4, UDP6],
+            ConnKind::Inet4 => &[TCP4, UDP4],
+            ConnKind::Inet6 => &[TCP6, UDP6],
+            ConnKind::Tcp   => &[TCP4, TCP6],
+            ConnKind::Tcp4  => &[TCP4],
+            ConnKind::Tcp6  => &[TCP6],
+            ConnKind::Udp   => &[UDP4, UDP6],
+            ConnKind::Udp4  => &[UDP4],
+            ConnKind::Udp6  => &[UDP6]
+        }
+    }
+}
+
+/// State of a socket, as defined in `include/net/tcp_states.h`
+///
+/// UDP sockets reuse these states: a UDP socket is `Close` when it is only
+/// bound, and `Established` once it has been connected to a remote address.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    NewSynRecv,
+    Unknown(u8)
+}
+
+impl TcpState {
+    /// Return the state with the number used by the kernel
+    pub fn from_number(number: u8) -> TcpState {
+        match number {
+            1  => TcpState::Established,
+            2  => TcpState::SynSent,
+            3  => TcpState::SynRecv,
+            4  => TcpState::FinWait1,
+            5  => TcpState::FinWait2,
+            6  => TcpState::TimeWait,
+            7  => TcpState::Close,
+            8  => TcpState::CloseWait,
+            9  => TcpState::LastAck,
+            10 => TcpState::Listen,
+            11 => TcpState::Closing,
+            12 => TcpState::NewSynRecv,
+            n  => TcpState::Unknown(n)
+        }
+    }
+}
+
+/// An internet socket
+///
+/// Read from a line of `/proc/net/tcp`, `/proc/net/udp` or their IPv6
+/// equivalents, e.g.
+///
+/// ```text
+///    0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 24067 1 ...
+/// ```
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct Connection {
+    /// Transport protocol of the socket
+    pub protocol: Protocol,
+
+    /// Local address and port, which is unspecified for an unbound socket
+    pub local: SocketAddr,
+
+    /// Remote address and port, which is unspecified unless connected
+    pub remote: SocketAddr,
+
+    /// State of the socket
+    pub state: TcpState,
+
+    /// Effective UID of the process that created the socket
+    pub uid: UID,
+
+    /// Inode of the socket, which matches the `socket:[inode]` link of each
+    /// file descriptor it is open on
+    ///
+    /// This is zero for sockets with no file descriptor, such as those in the
+    /// `TimeWait` state.
+    pub inode: u64,
+
+    /// PID of a process that has the socket open, if it has been looked up
+    pub pid: Option<PID>
+}
+
+/// Parse an address in `/proc/net/tcp`, such as `0100007F:1F90`
+///
+/// The address is printed as the hex digits of each 32 bit word of the
+/// address in host byte order, and the port in hex.
+fn parse_socket_addr(field: &str) -> Option<SocketAddr> {
+    let mut parts = field.splitn(2, ':');
+    let (address, port) = match (parts.next(), parts.next()) {
+        (Some(address), Some(port)) => (address, port),
+        _ => return None
+    };
+
+    let port = match u16::from_str_radix(port, 16) {
+        Ok(port) => port,
+        Err(_) => return None
+    };
+
+    let mut bytes = Vec::with_capacity(16);
+    for i in 0..address.len() / 8 {
+        match address.get(i * 8..i * 8 + 8).and_then(|w| u32::from_str_radix(w, 16).ok()) {
+            Some(word) => bytes.extend_from_slice(&word.to_ne_bytes()),
+            None => return None
+        }
+    }
+
+    let ip = match (address.len(), bytes.len()) {
+        (8, 4) => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+        (32, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&bytes);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        },
+        _ => return None
+    };
+
+    return Some(SocketAddr::new(ip, port));
+}
+
+/// Parse the contents of `/proc/net/{tcp,tcp6,udp,udp6}` into connections
+///
+/// The first line is a header. The owning process of each socket isn't
+/// listed, so `pid` is always `None`.
+pub fn parse_net_sockets(contents: &str, protocol: Protocol) -> Result<Vec<Connection>> {
+    let mut connections = Vec::new();
+
+    for line in contents.lines().skip(1) {
+        let invalid = || Error::new(ErrorKind::Other,
+            format!("Could not parse socket from /proc/net: {}", line.trim()));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return Err(invalid());
+        }
+
+        connections.push(Connection {
+            protocol:   protocol,
+            local:      try!(parse_socket_addr(fields[1]).ok_or_else(&invalid)),
+            remote:     try!(parse_socket_addr(fields[2]).ok_or_else(&invalid)),
+            state:      TcpState::from_number(try!(u8::from_str_radix(fields[3], 16)
+                .map_err(|_| invalid()))),
+            uid:        try!(fields[7].parse().map_err(|_| invalid())),
+            inode:      try!(fields[9].parse().map_err(|_| invalid())),
+            pid:        None
+        });
+    }
+
+    return Ok(connections);
+}
+
+/// Returns the internet sockets of the given kind
+///
+/// These are the sockets of the network namespace of the calling process.
+/// IPv6 tables are skipped if the kernel has no IPv6 support. Processes are
+/// not looked up, so `pid` is always `None`.
+pub fn connections(kind: ConnKind) -> Result<Vec<Connection>> {
+    let mut connections = Vec::new();
+
+    for &(name, protocol) in kind.tables() {
+        match read_file(&Path::new("/proc/net").join(name)) {
+            Ok(contents) => connections.extend(try!(parse_net_sockets(&contents, protocol))),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(e)
+        }
+    }
+
+    return Ok(connections);
+}
+
+/// Map the inode of each open socket to a process that has it open
+///
+/// Processes that exit during the scan, or whose file descriptors can't be
+/// read, are skipped. A socket open in several processes (e.g. after `fork`)
+/// is mapped to the one with the lowest PID.
+fn socket_owners() -> Result<HashMap<u64, PID>> {
+    let mut owners = HashMap::new();
+    let mut pids = try!(pids());
+    pids.sort();
+
+    for pid in pids {
+        let inodes = match Process::new(pid).and_then(|p| p.socket_inodes()) {
+            Ok(inodes) => inodes,
+            Err(ref e) if e.kind() == ErrorKind::NotFound
+                || e.kind() == ErrorKind::PermissionDenied => continue,
+            Err(e) => return Err(e)
+        };
+
+        for inode in inodes {
+            owners.entry(inode).or_insert(pid);
+        }
+    }
+
+    return Ok(owners);
+}
+
+/// Find the process with a socket of the given kind bound to a local port
+///
+/// A listening TCP socket is preferred over other sockets using the port,
+/// such as connections a server has accepted. Returns `Ok(None)` if no
+/// socket is using the port, or if the process that owns it can't be seen -
+/// unless this is run as root, only processes of the same user are checked.
+pub fn process_using_port(port: u16, kind: ConnKind) -> Result<Option<Process>> {
+    let mut sockets: Vec<Connection> = try!(connections(kind)).into_iter()
+        .filter(|c| c.local.port() == port && c.inode != 0)
+        .collect();
+    sockets.sort_by_key(|c| c.state != TcpState::Listen);
+
+    if sockets.is_empty() {
+        return Ok(None);
+    }
+
+    let owners = try!(socket_owners());
+    for socket in sockets.iter() {
+        if let Some(&pid) = owners.get(&socket.inode) {
+            match Process::new(pid) {
+                Ok(process) => return Ok(Some(process)),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
+    return Ok(None);
+}
+
+/// Returns the sockets accepting connections, with the process owning each
+///
+/// These are TCP sockets in the `Listen` state, and UDP sockets that are
+/// bound but not connected (the `Close` state), as shown by `ss -ltunp`.
+/// `pid` is set for each socket whose owner could be found - unless this is
+/// run as root, sockets of other users' processes are included with a `pid`
+/// of `None`.
+pub fn listening() -> Result<Vec<Connection>> {
+    let mut sockets: Vec<Connection> = try!(connections(ConnKind::Inet)).into_iter()
+        .filter(|c| match c.protocol {
+            Protocol::Tcp => c.state == TcpState::Listen,
+            Protocol::Udp => c.state == TcpState::Close && c.local.port() != 0
+        })
+        .collect();
+
+    let owners = try!(socket_owners());
+    for socket in sockets.iter_mut() {
+        socket.pid = owners.get(&socket.inode).cloned();
+    }
+
+    return Ok(sockets);
+}
@@ -2,12 +2,14 @@
 
 use std::fs::File;
 use std::io::{Read,Write};
-use std::io::{Error,ErrorKind,Result};
+use std::io::ErrorKind;
 use std::path::Path;
 use std::str::FromStr;
 
+use ::{Error,Result};
+
 pub fn write_pidfile(path: &Path) -> Result<()> {
-    return write!(&mut File::create(path).unwrap(), "{}", super::getpid());
+    return write!(&mut File::create(path).unwrap(), "{}", super::getpid()).map_err(Error::from);
 }
 
 pub fn read_pidfile(path: &Path) -> Result<super::PID> {
@@ -36,25 +36,64 @@
 //! struct does this conversion automatically, and all CPU time fields use the
 //! `f64` type.
 //!
+//! ### Files that don't apply to every process
+//!
+//! Some files in `/proc/[pid]` are missing or empty for some processes:
+//! kernel threads have no executable or command line, zombies have no
+//! working directory, and `io` is only present when the kernel was built with
+//! I/O accounting. Methods reading these return `Result<Option<T>>`, where
+//! `Ok(None)` means the information doesn't apply to a process that still
+//! exists, and `Err` is a real failure - including a `NotFound` error
+//! (`Error::NoSuchProcess`) when the process itself has exited, and
+//! `PermissionDenied` (`Error::AccessDenied`) when the file belongs to another
+//! user.
+//!
+//! ### Consistent reads
+//!
+//! Files in `/proc` are generated when they are read, so a file read in
+//! several parts can combine values from different moments. Each file is read
+//! with a single `read(2)` call when it fits the read buffer, which is grown
+//! and the file read again until it does, so the fields of a value like
+//! `Process` or `Status` are from the same snapshot. Files listing many
+//! records, like `maps` and `mountinfo`, are returned a page at a time by the
+//! kernel, so large ones are read in several calls and can change between
+//! them. Separate files are always read at different times.
+//!
 //! [rfc521]: https://github.com/rust-lang/rfcs/issues/521
 //! [array.c:361]: https://github.com/torvalds/linux/blob/4f671fe2f9523a1ea206f63fe60a7c7b3a56d5c7/fs/proc/array.c#L361
 //! [array.c:456]: https://github.com/torvalds/linux/blob/4f671fe2f9523a1ea206f63fe60a7c7b3a56d5c7/fs/proc/array.c#L456
 //!
 
-use std::fs::{self,read_dir};
+use std::cmp::{max,min,Ordering,Reverse};
+use std::collections::{BinaryHeap,HashMap,HashSet};
+use std::hash::{Hash,Hasher};
+use std::fs::{self,read_dir,File,OpenOptions};
+use std::ffi::{CString,OsString};
+use std::os::unix::ffi::OsStringExt;
 use std::os::unix::fs::MetadataExt;
-use std::io::{Error,ErrorKind,Result};
+use std::io::{ErrorKind,Write};
 use std::path::{Path,PathBuf};
-use std::str::FromStr;
+use std::ops::Deref;
+use std::ptr;
+use std::str::{self,FromStr};
 use std::string::ToString;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool,Ordering as AtomicOrdering};
+use std::sync::mpsc::{channel,Receiver};
+use std::thread;
+use std::time::{Duration,Instant,SystemTime,UNIX_EPOCH};
 use std::vec::Vec;
 
-use libc::consts::os::sysconf::{_SC_CLK_TCK,_SC_PAGESIZE};
-use libc::funcs::posix88::unistd::sysconf;
+use libc::{c_int,c_long,size_t};
+use libc::consts::os::posix88::{EACCES,EINVAL,EPERM,ESRCH};
 
-use ::{PID,UID,GID};
+use ::{Error,Result,PID,UID,GID};
+use ::network::{parse_net_dev,parse_net_sockets,ConnKind,Connection,NetIoCounters,TcpState};
 use ::pidfile::read_pidfile;
-use ::utils::read_file;
+use ::system::{boot_time,cpu_count,cpu_times};
+use ::users::{name_for_gid,name_for_uid,uid_for_name};
+use ::utils::{page_size,read_file,read_file_bytes,read_policy,read_whole_into,retry,ticks_per_second,
+    ticks_to_duration,with_file,with_file_bytes};
 
 fn procfs_path(pid: super::PID, name: &str) -> PathBuf {
     let mut path = PathBuf::new();
@@ -66,18 +105,70 @@ fn procfs_path(pid: super::PID, name: &str) -> PathBuf {
 
 /// Read a process' file from procfs - `/proc/[pid]/[name]`
 fn procfs(pid: super::PID, name: &str) -> Result<String> {
-    return read_file(&procfs_path(pid, name));
+    return read_file(&procfs_path(pid, name)).map_err(|e| Error::for_process(pid, e));
+}
+
+/// Parse a process' file from procfs without copying it - `/proc/[pid]/[name]`
+fn procfs_with<T, F>(pid: super::PID, name: &str, parse: F) -> Result<T>
+    where F: FnOnce(&str) -> Result<T> {
+    return with_file(&procfs_path(pid, name), parse).map_err(|e| Error::for_process(pid, e));
+}
+
+/// Parse a process' file from procfs as bytes without copying it - `/proc/[pid]/[name]`
+fn procfs_with_bytes<T, F>(pid: super::PID, name: &str, parse: F) -> Result<T>
+    where F: FnOnce(&[u8]) -> Result<T> {
+    return with_file_bytes(&procfs_path(pid, name), parse).map_err(|e| Error::for_process(pid, e));
+}
+
+/// Read a process' file from procfs as bytes - `/proc/[pid]/[name]`
+fn procfs_bytes(pid: super::PID, name: &str) -> Result<Vec<u8>> {
+    return read_file_bytes(&procfs_path(pid, name)).map_err(|e| Error::for_process(pid, e));
+}
+
+/// Treat a missing file as not applying to a process, if the process exists
+///
+/// See the module level notes on files that don't apply to every process.
+fn optional<T>(pid: PID, result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ref e) if e.kind() == ErrorKind::NotFound && procfs_path(pid, "").exists() => Ok(None),
+        Err(e) => Err(e)
+    }
+}
+
+/// Split the contents of `/proc/[pid]/cmdline` into a vector
+///
+/// A command line with no NUL bytes apart from trailing ones is a single
+/// argument - usually a title written by `setproctitle` - and is returned as it
+/// is rather than split on spaces.
+fn parse_cmdline(cmdline: &str) -> Option<Vec<String>> {
+    let title = cmdline.trim_right_matches('\0');
+
+    if cmdline == "" {
+        return None;
+    } else if !title.contains('\0') {
+        return Some(vec![title.to_string()]);
+    } else {
+        // Split terminator skips empty trailing substrings
+        let split = cmdline.split_terminator(
+            |c: char| c == '\0' || c == ' ');
+
+        // `split` returns a vector of slices viewing `cmdline`, so they
+        // get mapped to actuall strings before being returned as a vector.
+        return Some(split.map(|x| x.to_string()).collect());
+    }
 }
 
 /// Possible statuses for a process
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
 pub enum State {
     Running,
     Sleeping,
     Waiting,
     Zombie,
     Traced,
-    Paging
+    Paging,
+    Idle
 }
 
 impl State {
@@ -87,6 +178,8 @@ impl State {
     /// > sleeping in an interruptible wait, D is waiting in uninterruptible
     /// > disk sleep, Z is zombie, T is traced or stopped (on a signal), and W
     /// > is paging.
+    ///
+    /// Linux 4.14 and later also report idle kernel threads as I.
     fn from_char(state: char) -> Result<Self> {
         match state {
             'R' => Ok(State::Running),
@@ -95,6 +188,7 @@ impl State {
             'Z' => Ok(State::Zombie),
             'T' => Ok(State::Traced),
             'W' => Ok(State::Paging),
+            'I' => Ok(State::Idle),
              _  => Err(Error::new(ErrorKind::Other, "Invalid state character"))
         }
     }
@@ -120,15 +214,366 @@ impl ToString for State {
             &State::Waiting  => "D".to_string(),
             &State::Zombie   => "Z".to_string(),
             &State::Traced   => "T".to_string(),
-            &State::Paging   => "W".to_string()
+            &State::Paging   => "W".to_string(),
+            &State::Idle     => "I".to_string()
+        }
+    }
+}
+
+/// Signals that can be sent to a process
+///
+/// The numbers are those used by Linux on x86 and ARM, see `signal(7)`.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Signal {
+    Hangup = 1,
+    Interrupt = 2,
+    Quit = 3,
+    Illegal = 4,
+    Trap = 5,
+    Abort = 6,
+    Bus = 7,
+    FloatingPoint = 8,
+    Kill = 9,
+    User1 = 10,
+    SegmentationFault = 11,
+    User2 = 12,
+    Pipe = 13,
+    Alarm = 14,
+    Terminate = 15,
+    StackFault = 16,
+    Child = 17,
+    Continue = 18,
+    Stop = 19,
+    TerminalStop = 20,
+    TerminalInput = 21,
+    TerminalOutput = 22,
+    Urgent = 23,
+    CpuLimit = 24,
+    FileSizeLimit = 25,
+    VirtualAlarm = 26,
+    Profiling = 27,
+    WindowChange = 28,
+    Io = 29,
+    Power = 30,
+    BadSystemCall = 31
+}
+
+impl Signal {
+    /// Returns the signal number
+    pub fn number(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Returns the signal with the given number, if it is a standard signal
+    pub fn from_number(number: i32) -> Option<Signal> {
+        use self::Signal::*;
+
+        let signals = [
+            Hangup, Interrupt, Quit, Illegal, Trap, Abort, Bus, FloatingPoint,
+            Kill, User1, SegmentationFault, User2, Pipe, Alarm, Terminate,
+            StackFault, Child, Continue, Stop, TerminalStop, TerminalInput,
+            TerminalOutput, Urgent, CpuLimit, FileSizeLimit, VirtualAlarm,
+            Profiling, WindowChange, Io, Power, BadSystemCall];
+
+        signals.iter().find(|s| s.number() == number).map(|s| *s)
+    }
+
+    /// Returns the signals set in a mask from `/proc/[pid]/status`
+    ///
+    /// Bit `n - 1` of the mask is set for signal `n`. Real-time signals have
+    /// no `Signal` and are left out.
+    pub fn from_mask(mask: u64) -> Vec<Signal> {
+        (0..64).filter(|bit| mask & (1 << bit) != 0)
+            .filter_map(|bit| Signal::from_number(bit + 1))
+            .collect()
+    }
+}
+
+/// Signals pending, blocked, ignored and caught by a process
+///
+/// Read from the `Sig*` and `ShdPnd` lines of `/proc/[pid]/status`. These only
+/// include standard signals, the full masks are in `Status`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct SignalMasks {
+    /// Signals pending for the thread
+    pub pending: Vec<Signal>,
+
+    /// Signals pending for the process as a whole
+    pub shared_pending: Vec<Signal>,
+
+    /// Signals that are blocked, and stay pending until unblocked
+    pub blocked: Vec<Signal>,
+
+    /// Signals that are ignored
+    pub ignored: Vec<Signal>,
+
+    /// Signals that are caught by a handler
+    pub caught: Vec<Signal>
+}
+
+/// Capabilities a process can hold, see `capabilities(7)`
+///
+/// Capabilities added to the kernel after `CAP_CHECKPOINT_RESTORE` are given
+/// as `Unknown` with their number.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Capability {
+    Chown,
+    DacOverride,
+    DacReadSearch,
+    Fowner,
+    Fsetid,
+    Kill,
+    Setgid,
+    Setuid,
+    Setpcap,
+    LinuxImmutable,
+    NetBindService,
+    NetBroadcast,
+    NetAdmin,
+    NetRaw,
+    IpcLock,
+    IpcOwner,
+    SysModule,
+    SysRawio,
+    SysChroot,
+    SysPtrace,
+    SysPacct,
+    SysAdmin,
+    SysBoot,
+    SysNice,
+    SysResource,
+    SysTime,
+    SysTtyConfig,
+    Mknod,
+    Lease,
+    AuditWrite,
+    AuditControl,
+    Setfcap,
+    MacOverride,
+    MacAdmin,
+    Syslog,
+    WakeAlarm,
+    BlockSuspend,
+    AuditRead,
+    Perfmon,
+    Bpf,
+    CheckpointRestore,
+    Unknown(u32)
+}
+
+impl Capability {
+    /// Returns the capabilities known to this crate, in order of their numbers
+    fn known() -> &'static [Capability] {
+        use self::Capability::*;
+
+        static KNOWN: [Capability; 41] = [
+            Chown, DacOverride, DacReadSearch, Fowner, Fsetid, Kill, Setgid,
+            Setuid, Setpcap, LinuxImmutable, NetBindService, NetBroadcast,
+            NetAdmin, NetRaw, IpcLock, IpcOwner, SysModule, SysRawio,
+            SysChroot, SysPtrace, SysPacct, SysAdmin, SysBoot, SysNice,
+            SysResource, SysTime, SysTtyConfig, Mknod, Lease, AuditWrite,
+            AuditControl, Setfcap, MacOverride, MacAdmin, Syslog, WakeAlarm,
+            BlockSuspend, AuditRead, Perfmon, Bpf, CheckpointRestore];
+
+        &KNOWN
+    }
+
+    /// Returns the capability number, e.g. 12 for `CAP_NET_ADMIN`
+    pub fn number(&self) -> u32 {
+        match *self {
+            Capability::Unknown(number) => number,
+            capability => Capability::known().iter()
+                .position(|c| *c == capability).unwrap() as u32
         }
     }
+
+    /// Returns the capability with the given number
+    pub fn from_number(number: u32) -> Capability {
+        match Capability::known().get(number as usize) {
+            Some(capability) => *capability,
+            None => Capability::Unknown(number)
+        }
+    }
+
+    /// Returns the capabilities set in a mask from `/proc/[pid]/status`
+    pub fn from_mask(mask: u64) -> Vec<Capability> {
+        (0..64).filter(|bit| mask & (1 << bit) != 0).map(Capability::from_number).collect()
+    }
+}
+
+/// Capability sets of a process, see `capabilities(7)`
+///
+/// Read from the `Cap*` lines of `/proc/[pid]/status`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Capabilities {
+    /// Capabilities preserved across an `execve(2)`
+    pub inheritable: Vec<Capability>,
+
+    /// Capabilities the process may assume
+    pub permitted: Vec<Capability>,
+
+    /// Capabilities the kernel checks permissions against
+    pub effective: Vec<Capability>,
+
+    /// Capabilities the process and its children can ever gain
+    pub bounding: Vec<Capability>,
+
+    /// Capabilities kept across an `execve(2)` of an unprivileged program,
+    /// which is always empty before Linux 4.3
+    pub ambient: Vec<Capability>
+}
+
+/// Seccomp mode of a process, see `seccomp(2)`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum SeccompMode {
+    /// No system calls are filtered
+    Disabled,
+
+    /// Only `read`, `write`, `_exit` and `sigreturn` are allowed
+    Strict,
+
+    /// System calls are filtered by BPF programs
+    Filter
+}
+
+/// Resources that can be limited with `Process::set_rlimit`, see `getrlimit(2)`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum RLimit {
+    /// CPU time (seconds)
+    Cpu = 0,
+    /// Size of files the process may create (bytes)
+    FileSize = 1,
+    /// Size of the data segment (bytes)
+    Data = 2,
+    /// Size of the stack (bytes)
+    Stack = 3,
+    /// Size of core files (bytes)
+    Core = 4,
+    /// Resident set size, ignored since Linux 2.6 (bytes)
+    Rss = 5,
+    /// Number of processes for the real user ID of the process
+    NumProcesses = 6,
+    /// Number of open file descriptors
+    NumFiles = 7,
+    /// Memory that may be locked into RAM (bytes)
+    MemoryLock = 8,
+    /// Virtual memory size (bytes)
+    AddressSpace = 9,
+    /// Number of file locks
+    Locks = 10,
+    /// Number of queued signals for the real user ID of the process
+    SignalsPending = 11,
+    /// Size of POSIX message queues (bytes)
+    MessageQueue = 12,
+    /// Ceiling of the nice value, as `20 - nice`
+    Nice = 13,
+    /// Ceiling of the real-time priority
+    RealtimePriority = 14,
+    /// CPU time under real-time scheduling without blocking (microseconds)
+    RealtimeTime = 15
+}
+
+/// Value of a resource limit that is not limited
+pub const RLIM_INFINITY: u64 = !0;
+
+#[repr(C)]
+struct rlimit64 {
+    rlim_cur: u64,
+    rlim_max: u64
+}
+
+extern "C" {
+    fn prlimit64(pid: PID, resource: c_int, new_limit: *const rlimit64,
+                 old_limit: *mut rlimit64) -> c_int;
+    fn setpriority(which: c_int, who: UID, prio: c_int) -> c_int;
+    fn sched_getaffinity(pid: PID, cpusetsize: size_t, mask: *mut u64) -> c_int;
+    fn sched_setaffinity(pid: PID, cpusetsize: size_t, mask: *const u64) -> c_int;
+    fn prctl(option: c_int, ...) -> c_int;
+    fn syscall(number: c_long, ...) -> c_long;
+}
+
+/// System call number of `ioprio_set(2)`, which has no libc wrapper
+///
+/// Architectures using the generic system call table (`aarch64` and
+/// `riscv64`) share the number 30, and on any other this is `None` and
+/// `deprioritize` is unsupported.
+#[cfg(target_arch = "x86_64")]
+const SYS_IOPRIO_SET: Option<c_long> = Some(251);
+#[cfg(target_arch = "x86")]
+const SYS_IOPRIO_SET: Option<c_long> = Some(289);
+#[cfg(target_arch = "arm")]
+const SYS_IOPRIO_SET: Option<c_long> = Some(314);
+#[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
+const SYS_IOPRIO_SET: Option<c_long> = Some(30);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "arm",
+              target_arch = "aarch64", target_arch = "riscv64")))]
+const SYS_IOPRIO_SET: Option<c_long> = None;
+
+/// `which` argument of `ioprio_set` for a single thread
+const IOPRIO_WHO_PROCESS: c_int = 1;
+
+/// I/O priority of the idle scheduling class, which only gets disk time when
+/// no other process needs it
+const IOPRIO_IDLE: c_int = 3 << 13;
+
+/// Range of `/proc/[pid]/oom_score_adj`
+const OOM_SCORE_ADJ_MIN: i32 = -1000;
+const OOM_SCORE_ADJ_MAX: i32 = 1000;
+
+/// Range of the legacy `/proc/[pid]/oom_adj`, where -17 disables OOM killing
+const OOM_ADJ_MIN: i32 = -17;
+const OOM_ADJ_MAX: i32 = 15;
+
+/// Lowest `cpu.weight` of a cgroup v2, where the default is 100
+const CGROUP_CPU_WEIGHT_MIN: &'static str = "1";
+
+/// `prctl` option to set the name of the calling thread
+const PR_SET_NAME: c_int = 15;
+
+/// Size of a thread name in the kernel, including the terminating NUL byte
+const TASK_COMM_LEN: usize = 16;
+
+/// `which` argument of `setpriority` for a single process
+const PRIO_PROCESS: c_int = 0;
+
+/// Lowest and highest nice values
+const NICE_MIN: i64 = -20;
+const NICE_MAX: i64 = 19;
+
+/// The order `Process::kill_tree_with` signals a process tree in
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum KillOrder {
+    /// Signal the process, then its descendants from the top down
+    ///
+    /// With a fatal signal the parent can't fork new children once it has
+    /// been signalled, so this is the best choice for `Signal::Kill`.
+    ParentFirst,
+
+    /// Signal the deepest descendants first, and the process itself last
+    ///
+    /// This lets each parent see its children exit, which matters for
+    /// signals that ask processes to shut down gracefully. A parent that
+    /// restarts its children may replace them before it is signalled itself.
+    LeavesFirst
+}
+
+/// The value `top` sorts processes by, from highest to lowest
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum SortKey {
+    /// Percentage of a CPU used over a short sampling interval
+    CpuPercent,
+
+    /// Resident set size
+    Rss,
+
+    /// Virtual memory size
+    Vsize
 }
 
 /// Memory usage of a process
 ///
 /// Read from `/proc/[pid]/statm`
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,Default)]
 pub struct Memory {
     /// Total program size (bytes)
     pub size: u64,
@@ -139,355 +584,4834 @@ pub struct Memory {
     /// Shared pages (bytes)
     pub share: u64,
 
-    /// Text
+    /// Text (bytes)
     pub text: u64,
 
-    // /// Library (unused)
-    // pub lib: u64,
+    /// Library (bytes)
+    ///
+    /// This is unmaintained since Linux 2.6, and is always zero.
+    pub lib: u64,
 
-    /// Data + stack
+    /// Data + stack (bytes)
     pub data: u64,
 
-    // /// Dirty pages (unused)
-    // pub dt: u65
+    /// Dirty pages (bytes)
+    ///
+    /// This is unmaintained since Linux 2.6, and is always zero.
+    pub dt: u64
 }
 
 impl Memory {
     fn new(pid: PID) -> Result<Memory> {
-        let statm = try!(procfs(pid, "statm"));
-        let bytes: Vec<u64> = statm
-            .trim_right()
-            .split(" ")
-            .map(|n| n.parse().unwrap())
-            .collect();
+        procfs_with(pid, "statm", |statm| Memory::from_statm(statm, page_size()))
+    }
+
+    /// Parses the contents of `/proc/[pid]/statm`.
+    ///
+    /// `page_size` is the size of a page in bytes, which `Process::memory`
+    /// reads from `sysconf(_SC_PAGESIZE)`.
+    ///
+    /// Kernels before Linux 4.5 calculated the `data` field as
+    /// `total_vm - shared_vm`, which underflows to a number of pages close to
+    /// 2^64 when the shared count is larger than the total. Linux 4.5 changed
+    /// it to `data_vm + stack_vm` in "mm: rework virtual memory accounting".
+    /// No real size in pages can overflow a 64 bit size in bytes, so when a
+    /// field does it is reported as zero instead.
+    ///
+    /// Some kernel threads present a truncated file, so only the `size` and
+    /// `resident` fields are required and any missing trailing fields are
+    /// reported as zero. Returns an error with the kind `Other` if a required
+    /// field is missing or any field is not a number.
+    pub fn from_statm(statm: &str, page_size: u64) -> Result<Memory> {
+        let mut bytes: Vec<u64> = Vec::with_capacity(7);
+        for field in statm.split_whitespace() {
+            bytes.push(try!(field.parse().map_err(|_| {
+                Error::new(ErrorKind::Other,
+                    "Could not parse field from /proc/[pid]/statm")
+            })));
+        }
 
-        let page_size = unsafe { sysconf(_SC_PAGESIZE) } as u64;
-        //let ticks_per_second: f64 = unsafe { sysconf(_SC_CLK_TCK) } as f64;
+        if bytes.len() < 2 {
+            return Err(Error::new(ErrorKind::Other,
+                format!("Expected at least 2 fields in /proc/[pid]/statm, found {}",
+                    bytes.len())));
+        }
+        bytes.resize(7, 0);
 
         return Ok(Memory {
-            size:       bytes[0] * page_size,
-            resident:   bytes[1] * page_size,
-            share:      bytes[2] * page_size,
-            text:       bytes[3] * page_size,
-            // lib:     bytes[4] * page_size,
-            data:       bytes[5] * page_size,
-            // dt:      bytes[6] * page_size
+            size:       pages_to_bytes(bytes[0], page_size),
+            resident:   pages_to_bytes(bytes[1], page_size),
+            share:      pages_to_bytes(bytes[2], page_size),
+            text:       pages_to_bytes(bytes[3], page_size),
+            lib:        pages_to_bytes(bytes[4], page_size),
+            data:       pages_to_bytes(bytes[5], page_size),
+            dt:         pages_to_bytes(bytes[6], page_size)
         });
     }
 }
 
-/// Information about a process gathered from `/proc/[pid]/stat`.
+/// Detailed memory usage of a process, including PSS and USS
 ///
-/// **IMPORTANT**: See the module level notes for information on the types used
-/// by this struct, as some do not match those used by `/proc/[pid]/stat`.
-#[derive(Clone,Debug)]
-pub struct Process {
-    /// PID of the process
-    pub pid: PID,
+/// Read from `/proc/[pid]/smaps_rollup`, or by summing `/proc/[pid]/smaps`.
+/// All sizes are in bytes.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct MemoryFull {
+    /// Resident set size
+    pub rss: u64,
 
-    /// UID of the process
-    pub uid: UID,
+    /// Proportional set size, where each shared page is divided between the
+    /// processes that map it
+    pub pss: u64,
 
-    /// UID of the process
-    pub gid: GID,
+    /// Unique set size, the memory that would be freed if the process exited
+    pub uss: u64,
 
-    /// Filename of the executable
-    pub comm: String,
+    /// Shared pages that haven't been written to
+    pub shared_clean: u64,
 
-    /// State of the process as an enum
-    pub state: State,
+    /// Shared pages that have been written to
+    pub shared_dirty: u64,
 
-    /// PID of the parent process
-    pub ppid: PID,
+    /// Private pages that haven't been written to
+    pub private_clean: u64,
 
-    /// Process group ID
-    pub pgrp: i32,
+    /// Private pages that have been written to
+    pub private_dirty: u64,
 
-    /// Session ID
-    pub session: i32,
+    /// Memory swapped out
+    pub swap: u64
+}
 
-    /// Controlling terminal of the process [TODO: Actually two numbers]
-    pub tty_nr: i32,
+impl MemoryFull {
+    /// Parses the contents of `/proc/[pid]/smaps` or `/proc/[pid]/smaps_rollup`.
+    ///
+    /// The sizes of every mapping are summed, and lines other than sizes in kB
+    /// (such as the header line of each mapping) are ignored.
+    pub fn from_smaps(smaps: &str) -> Result<MemoryFull> {
+        let mut memory = MemoryFull::default();
 
-    /// ID of the foreground group of the controlling terminal
-    pub tpgid: i32,
+        for line in smaps.lines() {
+            let mut parts = line.splitn(2, ':');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if value.trim_right().ends_with(" kB") => (key, value),
+                _ => continue
+            };
 
-    /// Kernel flags for the process
-    pub flags: u32,
+            let field = match key {
+                "Rss" => &mut memory.rss,
+                "Pss" => &mut memory.pss,
+                "Shared_Clean" => &mut memory.shared_clean,
+                "Shared_Dirty" => &mut memory.shared_dirty,
+                "Private_Clean" => &mut memory.private_clean,
+                "Private_Dirty" => &mut memory.private_dirty,
+                "Swap" => &mut memory.swap,
+                _ => continue
+            };
 
-    /// Minor faults
-    pub minflt: u64,
+            let kb: u64 = try!(value.trim().trim_right_matches("kB").trim().parse().map_err(|_| {
+                Error::new(ErrorKind::Other, format!("Could not parse {} from /proc/[pid]/smaps", key))
+            }));
+            *field += kb * 1024;
+        }
 
-    /// Minor faults by child processes
-    pub cminflt: u64,
+        memory.uss = memory.private_clean + memory.private_dirty;
+        return Ok(memory);
+    }
+}
 
-    /// Major faults
-    pub majflt: u64,
+/// Convert a number of pages to bytes, or zero if the result would overflow
+fn pages_to_bytes(pages: u64, page_size: u64) -> u64 {
+    pages.checked_mul(page_size).unwrap_or(0)
+}
 
-    /// Major faults by child processes
-    pub cmajflt: u64,
+/// Read an audit ID, which is `(u32)-1` if it is unset
+fn read_audit_id(pid: PID, name: &str) -> Result<Option<u32>> {
+    let id: u32 = try!(try!(procfs(pid, name)).trim().parse().map_err(|_| {
+        Error::new(ErrorKind::Other, format!("Could not parse /proc/[pid]/{}", name))
+    }));
 
-    /// Time scheduled in user mode (seconds)
-    pub utime: f64,
+    return Ok(if id == !0 { None } else { Some(id) });
+}
 
-    /// Time scheduled in kernel mode (seconds)
-    pub stime: f64,
+/// Return the function of the top frame of `/proc/[pid]/stack`
+///
+/// Each line is in the form `[<0>] do_select+0x6c5/0x7a0`.
+fn stack_top_function(stack: &str) -> Option<String> {
+    let frame = match stack.lines().next() {
+        Some(frame) => frame,
+        None => return None
+    };
 
-    /// Time waited-for child processes were scheduled in user mode (seconds)
-    pub cutime: f64,
+    let symbol = frame.splitn(2, "] ").nth(1).unwrap_or(frame).trim();
+    let function = symbol.split('+').next().unwrap_or(symbol);
+    return if function.is_empty() { None } else { Some(function.to_string()) };
+}
 
-    /// Time waited-for child processes were scheduled in kernel mode (seconds)
-    pub cstime: f64,
+/// Suffix the kernel appends to links whose target has been unlinked
+const DELETED_SUFFIX: &'static str = " (deleted)";
 
-    /// Priority value (-100..-2 | 0..39)
-    pub priority: i64,
+/// Remove `DELETED_SUFFIX` from the target of a link
+///
+/// A file that really is named with the suffix is indistinguishable from one
+/// that was deleted, and is shortened too.
+fn strip_deleted_suffix(path: PathBuf) -> PathBuf {
+    let mut bytes = path.into_os_string().into_vec();
+    if bytes.ends_with(DELETED_SUFFIX.as_bytes()) {
+        let len = bytes.len() - DELETED_SUFFIX.len();
+        bytes.truncate(len);
+    }
+    return PathBuf::from(OsString::from_vec(bytes));
+}
 
-    /// Nice value (-20..19)
-    pub nice: i64,
+/// A line of `/proc/[pid]/fdinfo/[fd]` specific to the type of descriptor
+///
+/// Examples are the `eventfd-count` line of an eventfd, one `tfd` line for each
+/// descriptor watched by an epoll instance, and one `inotify` line for each
+/// inotify watch.
+#[derive(Clone,Debug)]
+pub struct FdInfoEntry {
+    /// Name of the first field on the line, e.g. `tfd` or `inotify`
+    pub name: String,
 
-    /// Number of threads in the process
-    pub num_threads: i64,
+    /// Each `key:value` pair on the line, including the first
+    pub fields: Vec<(String, String)>
+}
 
-    // /// Unmaintained field since linux 2.6.17, always 0
-    // itrealvalue: i64,
+impl FdInfoEntry {
+    /// Return the value of a field on this line
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|f| f.0 == key).map(|f| &f.1[..])
+    }
+}
 
-    /// Time the process was started after system boot (clock ticks)
-    pub starttime: u64,
+impl FromStr for FdInfoEntry {
+    type Err = Error;
 
-    /// Virtual memory size in bytes
-    pub vsize: u64,
+    /// Parses a line of whitespace separated `key: value` or `key:value` pairs
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields = Vec::new();
+        let mut tokens = s.split_whitespace();
 
-    /// Resident Set Size (pages) [TODO: Calculate size in bytes]
-    pub rss: i64,
+        while let Some(token) = tokens.next() {
+            if token.ends_with(':') {
+                let value = tokens.next().unwrap_or("");
+                fields.push((token[..token.len()-1].to_string(), value.to_string()));
+            } else if let Some(i) = token.find(':') {
+                fields.push((token[..i].to_string(), token[i+1..].to_string()));
+            } else {
+                fields.push((token.to_string(), String::new()));
+            }
+        }
 
-    /// Current soft limit on process RSS (bytes)
-    pub rsslim: u64,
+        let name = match fields.first() {
+            Some(field) => field.0.clone(),
+            None => return Err(Error::new(ErrorKind::Other, "Empty fdinfo line"))
+        };
 
-    // These values are memory addresses
-    startcode: u64,
-    endcode: u64,
-    startstack: u64,
-    kstkesp: u64,
-    kstkeip: u64,
+        return Ok(FdInfoEntry { name: name, fields: fields });
+    }
+}
 
-    // /// Signal bitmaps
-    // /// These are obselete, use `/proc/[pid]/status` instead
-    // signal: u64,
-    // blocked: u64,
-    // sigignore: u64,
-    // sigcatch: u64,
+/// Information about a file descriptor
+///
+/// Read from `/proc/[pid]/fdinfo/[fd]`
+#[derive(Clone,Debug)]
+pub struct FdInfo {
+    /// Current file offset (bytes)
+    pub pos: u64,
 
-    /// Channel the process is waiting on (address of a system call)
-    pub wchan: u64,
+    /// Flags the file was opened with, see `open(2)`
+    pub flags: u32,
 
-    // /// Number of pages swapped (not maintained)
-    // pub nswap: u64,
+    /// ID of the mount containing the file (since Linux 3.15)
+    pub mnt_id: Option<i32>,
 
-    // /// Number of pages swapped for child processes (not maintained)
-    // pub cnswap: u64,
+    /// Any further lines, which depend on the type of file descriptor
+    pub entries: Vec<FdInfoEntry>
+}
 
-    /// Signal sent to parent when process dies
-    pub exit_signal: i32,
+impl FdInfo {
+    fn new(pid: PID, fd: i32) -> Result<FdInfo> {
+        procfs_with(pid, &format!("fdinfo/{}", fd), FromStr::from_str)
+    }
 
-    /// Number of the CPU the process was last executed on
-    pub processor: i32,
+    /// Return the first entry with the given name
+    pub fn entry(&self, name: &str) -> Option<&FdInfoEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+}
 
-    /// Real-time scheduling priority (0 | 1..99)
-    pub rt_priority: u32,
+impl FromStr for FdInfo {
+    type Err = Error;
 
-    /// Scheduling policy
-    pub policy: u32,
+    fn from_str(s: &str) -> Result<Self> {
+        let mut pos = None;
+        let mut flags = None;
+        let mut mnt_id = None;
+        let mut entries = Vec::new();
 
-    /// Aggregated block I/O delays (clock ticks)
-    pub delayacct_blkio_ticks: u64,
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some("pos"), Some(value)) => {
+                    pos = Some(try!(u64::from_str(value.trim()).map_err(|_| {
+                        Error::new(ErrorKind::Other, "Could not parse fdinfo pos")
+                    })));
+                },
+                (Some("flags"), Some(value)) => {
+                    flags = Some(try!(u32::from_str_radix(value.trim(), 8).map_err(|_| {
+                        Error::new(ErrorKind::Other, "Could not parse fdinfo flags")
+                    })));
+                },
+                (Some("mnt_id"), Some(value)) => {
+                    mnt_id = Some(try!(i32::from_str(value.trim()).map_err(|_| {
+                        Error::new(ErrorKind::Other, "Could not parse fdinfo mnt_id")
+                    })));
+                },
+                _ => entries.push(try!(FromStr::from_str(line)))
+            }
+        }
 
-    /// Guest time of the process (seconds)
-    pub guest_time: f64,
+        return match (pos, flags) {
+            (Some(pos), Some(flags)) => Ok(FdInfo {
+                pos: pos,
+                flags: flags,
+                mnt_id: mnt_id,
+                entries: entries
+            }),
+            _ => Err(Error::new(ErrorKind::Other,
+                "Missing pos or flags field in fdinfo"))
+        };
+    }
+}
 
-    /// Guest time of the process's children (seconds)
-    pub cguest_time: f64,
+/// An open file descriptor of a process
+///
+/// Read from `/proc/[pid]/fd/[fd]` and `/proc/[pid]/fdinfo/[fd]`
+#[derive(Clone,Debug)]
+pub struct OpenFile {
+    /// File descriptor number
+    pub fd: i32,
 
-    // More memory addresses
-    start_data: u64,
-    end_data: u64,
-    start_brk: u64,
-    arg_start: u64,
-    arg_end: u64,
-    env_start: u64,
-    env_end: u64,
+    /// Target of the file descriptor link
+    ///
+    /// This is not always a filesystem path, e.g. `socket:[12345]`.
+    pub path: PathBuf,
 
-    /// The thread's exit status
-    pub exit_code: i32
+    /// Current file offset (bytes)
+    pub position: u64,
+
+    /// Flags the file was opened with, see `open(2)`
+    pub flags: u32,
+
+    /// Access mode derived from `flags`, in the style of `fopen(3)`
+    pub mode: String
 }
 
-/// TODO: This should use `try!` instead of `unwrap()`
-macro_rules! from_str { ($field:expr) => (FromStr::from_str($field).unwrap()) }
+impl OpenFile {
+    fn new(pid: PID, fd: i32, path: PathBuf) -> Result<OpenFile> {
+        let fdinfo = try!(FdInfo::new(pid, fd));
 
-impl Process {
-    /// Parses a process name
-    ///
-    /// Process names are surrounded by `()` characters, which are removed.
-    fn parse_comm(s: &str) -> String {
-        let comm = s.to_string();
-        comm[1..comm.len()-1].to_string()
+        return Ok(OpenFile {
+            fd: fd,
+            path: path,
+            position: fdinfo.pos,
+            flags: fdinfo.flags,
+            mode: OpenFile::mode_from_flags(fdinfo.flags).to_string()
+        });
     }
 
-    /// Attempts to read process information from `/proc/[pid]/stat`.
-    ///
-    /// `/stat` is seperated by spaces and contains a trailing newline.
-    ///
-    /// This should return a psutil/process specific error type, so that  errors
-    /// can be raised by `FromStr` too
-    pub fn new(pid: PID) -> Result<Process> {
-        let path = procfs_path(pid, "stat");
-        let stat = try!(procfs(pid, "stat"));
-        let stat: Vec<&str> = stat[0..stat.len()-1].split(' ').collect();
-        let meta = try!(fs::metadata(path));
+    /// Converts open flags to the mode string `fopen(3)` would have used
+    fn mode_from_flags(flags: u32) -> &'static str {
+        use libc::consts::os::posix01::O_ACCMODE;
+        use libc::consts::os::posix88::{O_APPEND,O_RDONLY,O_WRONLY};
 
-        // This may only be the case for Linux, but this can be removed or
-        // changed when/if support for other kernels is needed
-        if !stat.len() == 52 {
+        let append = flags & O_APPEND as u32 != 0;
+        match (flags & O_ACCMODE as u32) as i32 {
+            O_RDONLY => "r",
+            O_WRONLY => if append { "a" } else { "w" },
+            _        => if append { "a+" } else { "r+" }
+        }
+    }
+}
+
+/// I/O a process has done
+///
+/// Read from `/proc/[pid]/io`
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct IoCounters {
+    /// Bytes read with system calls like `read(2)`, including from the cache
+    pub rchar: u64,
+
+    /// Bytes written with system calls like `write(2)`, including to the cache
+    pub wchar: u64,
+
+    /// Number of read system calls
+    pub syscr: u64,
+
+    /// Number of write system calls
+    pub syscw: u64,
+
+    /// Bytes actually fetched from storage
+    pub read_bytes: u64,
+
+    /// Bytes sent to storage, or that will be sent when the cache is flushed
+    pub write_bytes: u64,
+
+    /// Bytes from `write_bytes` that were never sent because their file was
+    /// truncated first
+    pub cancelled_write_bytes: u64
+}
+
+impl FromStr for IoCounters {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for line in s.lines() {
+            let mut parts = line.splitn(2, ':');
+            if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                fields.insert(key, value.trim());
+            }
+        }
+
+        let field = |key: &str| -> Result<u64> {
+            match fields.get(key).map(|value| value.parse()) {
+                Some(Ok(value)) => Ok(value),
+                _ => Err(Error::new(ErrorKind::Other,
+                    format!("Could not parse {} from /proc/[pid]/io", key)))
+            }
+        };
+
+        return Ok(IoCounters {
+            rchar:                  try!(field("rchar")),
+            wchar:                  try!(field("wchar")),
+            syscr:                  try!(field("syscr")),
+            syscw:                  try!(field("syscw")),
+            read_bytes:             try!(field("read_bytes")),
+            write_bytes:            try!(field("write_bytes")),
+            cancelled_write_bytes:  try!(field("cancelled_write_bytes"))
+        });
+    }
+}
+
+/// A mount in the mount namespace of a process
+///
+/// Read from a line of `/proc/[pid]/mountinfo`, e.g.
+///
+/// ```text
+/// 36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+/// ```
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct MountInfo {
+    /// Unique ID of the mount
+    pub mount_id: u32,
+
+    /// ID of the parent mount, or of itself for the root of the namespace
+    pub parent_id: u32,
+
+    /// Major number of the device the filesystem is on
+    pub major: u32,
+
+    /// Minor number of the device the filesystem is on
+    pub minor: u32,
+
+    /// Directory within the filesystem that is the root of the mount
+    pub root: PathBuf,
+
+    /// Where the mount is, relative to the root of the process
+    pub mount_point: PathBuf,
+
+    /// Per-mount options, e.g. `rw,noatime`
+    pub options: String,
+
+    /// Zero or more `tag[:value]` fields, e.g. `shared:1` or `master:2`
+    pub optional_fields: Vec<String>,
+
+    /// Filesystem type, e.g. `ext4` or `fuse.sshfs`
+    pub fs_type: String,
+
+    /// Filesystem-specific source, e.g. `/dev/sda1`, or `none`
+    pub source: String,
+
+    /// Per-superblock options, e.g. `rw,errors=remount-ro`
+    pub super_options: String
+}
+
+/// Replace the `\ooo` octal escapes the kernel uses for spaces and other
+/// special characters in `/proc/[pid]/mountinfo`
+fn unescape_octal(field: &str) -> Vec<u8> {
+    let bytes = field.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = &bytes[i..];
+        if escape.len() >= 4 && escape[0] == b'\\'
+            && escape[1..4].iter().all(|b| *b >= b'0' && *b <= b'7') {
+            result.push(escape[1..4].iter().fold(0u8, |n, b| n.wrapping_mul(8) + (b - b'0')));
+            i += 4;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    return result;
+}
+
+impl FromStr for MountInfo {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let invalid = || Error::new(ErrorKind::Other,
+            "Could not parse line from /proc/[pid]/mountinfo");
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        // The optional fields are terminated by a single hyphen, and are
+        // followed by exactly three more fields
+        let separator = try!(fields.iter().skip(6).position(|f| *f == "-")
+            .map(|i| i + 6).ok_or_else(&invalid));
+        if separator + 4 != fields.len() {
+            return Err(invalid());
+        }
+
+        let mut device = fields[2].splitn(2, ':');
+        let major = try!(device.next().and_then(|n| n.parse().ok()).ok_or_else(&invalid));
+        let minor = try!(device.next().and_then(|n| n.parse().ok()).ok_or_else(&invalid));
+
+        return Ok(MountInfo {
+            mount_id:           try!(fields[0].parse().map_err(|_| invalid())),
+            parent_id:          try!(fields[1].parse().map_err(|_| invalid())),
+            major:              major,
+            minor:              minor,
+            root:               PathBuf::from(OsString::from_vec(unescape_octal(fields[3]))),
+            mount_point:        PathBuf::from(OsString::from_vec(unescape_octal(fields[4]))),
+            options:            fields[5].to_string(),
+            optional_fields:    fields[6..separator].iter().map(|f| f.to_string()).collect(),
+            fs_type:            fields[separator + 1].to_string(),
+            source:             String::from_utf8_lossy(&unescape_octal(fields[separator + 2])).into_owned(),
+            super_options:      fields[separator + 3].to_string()
+        });
+    }
+}
+
+/// Access permissions of a memory mapping
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,Hash)]
+pub struct Perms {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+
+    /// `true` for a shared mapping, `false` for a private copy-on-write one
+    pub shared: bool
+}
+
+impl FromStr for Perms {
+    type Err = Error;
+
+    /// Parse permissions in the format used by `/proc/[pid]/maps`, e.g. `r-xp`
+    fn from_str(perms: &str) -> Result<Self> {
+        let bytes = perms.as_bytes();
+        if bytes.len() != 4 {
             return Err(Error::new(ErrorKind::Other,
-                "Unexpected number of fields from /proc/[pid]/stat"));
+                format!("Could not parse permissions {:?} from /proc/[pid]/maps", perms)));
+        }
+
+        return Ok(Perms {
+            read:       bytes[0] == b'r',
+            write:      bytes[1] == b'w',
+            execute:    bytes[2] == b'x',
+            shared:     bytes[3] == b's'
+        });
+    }
+}
+
+/// A memory mapping of a process
+///
+/// Read from a line of `/proc/[pid]/maps`, e.g.
+///
+/// ```text
+/// 00400000-00452000 r-xp 00000000 08:02 173521      /usr/bin/dbus-daemon
+/// ```
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct MemoryMap {
+    /// Address of the start of the mapping
+    pub start: u64,
+
+    /// Address of the end of the mapping, which is not part of it
+    pub end: u64,
+
+    /// Access permissions
+    pub perms: Perms,
+
+    /// Offset into the mapped file
+    pub offset: u64,
+
+    /// Major number of the device the mapped file is on
+    pub major: u32,
+
+    /// Minor number of the device the mapped file is on
+    pub minor: u32,
+
+    /// Inode of the mapped file, or 0 for anonymous memory
+    pub inode: u64,
+
+    /// The mapped file, a pseudo-path such as `[heap]` or `[stack]`, or
+    /// `None` for anonymous memory
+    ///
+    /// The kernel appends ` (deleted)` if the file has been unlinked.
+    pub path: Option<PathBuf>
+}
+
+impl MemoryMap {
+    /// Return the size of the mapping in bytes.
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+impl FromStr for MemoryMap {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let invalid = || Error::new(ErrorKind::Other,
+            format!("Could not parse line from /proc/[pid]/maps: {}", line));
+
+        // The path is everything after the fifth field, and can contain spaces
+        let mut fields = line.splitn(6, ' ');
+        let mut next = || fields.next().ok_or_else(&invalid);
+        let (range, perms, offset, device, inode) =
+            (try!(next()), try!(next()), try!(next()), try!(next()), try!(next()));
+        let path = fields.next().map(|p| p.trim_left()).unwrap_or("");
+
+        let mut range = range.splitn(2, '-');
+        let mut device = device.splitn(2, ':');
+        let hex = |field: Option<&str>| -> Result<u64> {
+            field.and_then(|f| u64::from_str_radix(f, 16).ok()).ok_or_else(&invalid)
+        };
+
+        return Ok(MemoryMap {
+            start:      try!(hex(range.next())),
+            end:        try!(hex(range.next())),
+            perms:      try!(Perms::from_str(perms)),
+            offset:     try!(hex(Some(offset))),
+            major:      try!(hex(device.next())) as u32,
+            minor:      try!(hex(device.next())) as u32,
+            inode:      try!(inode.parse().map_err(|_| invalid())),
+            path:       if path.is_empty() { None } else { Some(PathBuf::from(path)) }
+        });
+    }
+}
+
+/// The flags of a memory mapping, from the `VmFlags` line of `/proc/[pid]/smaps`
+///
+/// Each flag is a two letter code, such as `rd` for readable or `lo` for
+/// locked, and the set of codes depends on the kernel version, so any flag
+/// can be checked with `contains`. See `proc(5)` for the full list.
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct VmFlags {
+    flags: Vec<String>
+}
+
+impl VmFlags {
+    /// Return `true` if the mapping has the flag with the given code.
+    pub fn contains(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// Iterate over the codes of the flags, in the order the kernel lists them.
+    pub fn iter(&self) -> ::std::slice::Iter<'_, String> {
+        self.flags.iter()
+    }
+
+    /// Return `true` if the pages are locked in memory (`lo`), as by `mlock(2)`.
+    pub fn locked(&self) -> bool {
+        self.contains("lo")
+    }
+
+    /// Return `true` if the mapping is backed by hugetlbfs pages (`ht`).
+    pub fn hugetlb(&self) -> bool {
+        self.contains("ht")
+    }
+
+    /// Return `true` if transparent huge pages were advised for the mapping
+    /// with `madvise(MADV_HUGEPAGE)` (`hg`).
+    pub fn thp_advised(&self) -> bool {
+        self.contains("hg")
+    }
+
+    /// Return `true` if transparent huge pages were disabled for the mapping
+    /// with `madvise(MADV_NOHUGEPAGE)` (`nh`).
+    pub fn thp_disabled(&self) -> bool {
+        self.contains("nh")
+    }
+}
+
+impl FromStr for VmFlags {
+    type Err = Error;
+
+    /// Parse the value of a `VmFlags` line, e.g. `rd wr mr mw me ac`
+    fn from_str(flags: &str) -> Result<Self> {
+        Ok(VmFlags { flags: flags.split_whitespace().map(|f| f.to_string()).collect() })
+    }
+}
+
+/// A memory mapping of a process with its memory usage
+///
+/// Read from an entry of `/proc/[pid]/smaps`, which has a line in the format
+/// of `/proc/[pid]/maps` followed by a line for each field.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct SmapsEntry {
+    /// The mapping
+    pub map: MemoryMap,
+
+    /// Memory usage of the mapping
+    pub memory: MemoryFull,
+
+    /// Memory backed by transparent huge pages (bytes)
+    pub anon_huge_pages: u64,
+
+    /// Whether the mapping can be backed by transparent huge pages
+    ///
+    /// This is `None` on kernels before 4.20, which don't report it.
+    pub thp_eligible: Option<bool>,
+
+    /// Flags of the mapping
+    ///
+    /// These are empty on kernels before 3.8, which don't report them.
+    pub vm_flags: VmFlags
+}
+
+impl SmapsEntry {
+    /// Parses the contents of `/proc/[pid]/smaps` into an entry for each mapping.
+    ///
+    /// Returns an error with the kind `Other` if a mapping line or a field
+    /// the entry holds can't be parsed.
+    pub fn from_smaps(smaps: &str) -> Result<Vec<SmapsEntry>> {
+        let mut entries = Vec::new();
+        let mut fields = String::new();
+        let mut current: Option<SmapsEntry> = None;
+
+        for line in smaps.lines() {
+            // Field names contain no `-`, and the address range always does
+            let is_header = line.split_whitespace().next()
+                .map_or(false, |first| first.contains('-') && !first.ends_with(':'));
+
+            if is_header {
+                if let Some(entry) = current.take() {
+                    entries.push(try!(entry.finish(&fields)));
+                }
+                fields.clear();
+                current = Some(SmapsEntry {
+                    map: try!(FromStr::from_str(line)),
+                    memory: MemoryFull::default(),
+                    anon_huge_pages: 0,
+                    thp_eligible: None,
+                    vm_flags: VmFlags::default()
+                });
+                continue;
+            }
+
+            let entry = match current.as_mut() {
+                Some(entry) => entry,
+                None => return Err(Error::new(ErrorKind::Other,
+                    "Found a field before the first mapping in /proc/[pid]/smaps"))
+            };
+
+            let mut parts = line.splitn(2, ':');
+            let (key, value) = (parts.next().unwrap_or(""), parts.next().unwrap_or("").trim());
+            let invalid = || Error::new(ErrorKind::Other,
+                format!("Could not parse {} from /proc/[pid]/smaps", key));
+
+            match key {
+                "AnonHugePages" => {
+                    let kb: u64 = try!(value.trim_right_matches("kB").trim().parse().map_err(|_| invalid()));
+                    entry.anon_huge_pages = kb * 1024;
+                },
+                "THPeligible" => entry.thp_eligible = Some(try!(match value {
+                    "0" => Ok(false),
+                    "1" => Ok(true),
+                    _ => Err(invalid())
+                })),
+                "VmFlags" => entry.vm_flags = try!(FromStr::from_str(value)),
+                _ => ()
+            }
+
+            fields.push_str(line);
+            fields.push('\n');
+        }
+
+        if let Some(entry) = current {
+            entries.push(try!(entry.finish(&fields)));
+        }
+
+        return Ok(entries);
+    }
+
+    /// Fill in the memory usage from the field lines of the entry
+    fn finish(mut self, fields: &str) -> Result<SmapsEntry> {
+        self.memory = try!(MemoryFull::from_smaps(fields));
+        return Ok(self);
+    }
+}
+
+/// The NUMA placement of a memory mapping of a process
+///
+/// Read from a line of `/proc/[pid]/numa_maps`, e.g.
+///
+/// ```text
+/// 7f8b9c000000 default file=/usr/lib/libc.so.6 mapped=37 mapmax=4 N0=20 N1=17 kernelpagesize_kB=4
+/// ```
+///
+/// After the address and policy each line has a variable set of fields,
+/// depending on the mapping and the kernel version. The page counts per node
+/// and the page size are parsed into fields, and the other numeric fields
+/// (such as `anon`, `dirty` and `mapped`) are kept by name in `values`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct NumaMap {
+    /// Address of the start of the mapping
+    pub address: u64,
+
+    /// NUMA memory policy of the mapping, e.g. `default`, `bind:0-1`,
+    /// `interleave:0-3` or `prefer:1`
+    pub policy: String,
+
+    /// The mapped file, or `None` for anonymous memory
+    pub file: Option<PathBuf>,
+
+    /// Number of pages on each node with any pages of the mapping, as
+    /// `(node, pages)` in the order the kernel lists them
+    pub nodes: Vec<(usize, u64)>,
+
+    /// Size of the mapping's pages in bytes, if given
+    pub kernel_page_size: Option<u64>,
+
+    /// Fields without a value, such as `heap`, `stack` and `huge`
+    pub flags: Vec<String>,
+
+    /// Other numeric `key=value` fields, by key
+    pub values: HashMap<String, u64>
+}
+
+impl NumaMap {
+    /// Return the number of pages of the mapping on all nodes.
+    pub fn pages(&self) -> u64 {
+        self.nodes.iter().map(|&(_, pages)| pages).sum()
+    }
+
+    /// Return the number of pages of the mapping on the given node.
+    pub fn pages_on(&self, node: usize) -> u64 {
+        self.nodes.iter().filter(|&&(n, _)| n == node).map(|&(_, pages)| pages).sum()
+    }
+}
+
+impl FromStr for NumaMap {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let invalid = || Error::new(ErrorKind::Other,
+            format!("Could not parse line from /proc/[pid]/numa_maps: {}", line));
+
+        let mut fields = line.split_whitespace();
+        let address = try!(fields.next()
+            .and_then(|address| u64::from_str_radix(address, 16).ok())
+            .ok_or_else(&invalid));
+        let policy = try!(fields.next().ok_or_else(&invalid));
+
+        let mut map = NumaMap {
+            address: address,
+            policy: policy.to_string(),
+            file: None,
+            nodes: Vec::new(),
+            kernel_page_size: None,
+            flags: Vec::new(),
+            values: HashMap::new()
+        };
+
+        for field in fields {
+            let mut parts = field.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key, value),
+                _ => {
+                    map.flags.push(field.to_string());
+                    continue;
+                }
+            };
+
+            if key == "file" {
+                map.file = Some(PathBuf::from(value));
+                continue;
+            }
+
+            let number: u64 = try!(value.parse().map_err(|_| invalid()));
+            let node = if key.starts_with('N') { key[1..].parse().ok() } else { None };
+            match node {
+                Some(node) => map.nodes.push((node, number)),
+                None if key == "kernelpagesize_kB" => map.kernel_page_size = Some(number * 1024),
+                None => { map.values.insert(key.to_string(), number); }
+            }
+        }
+
+        return Ok(map);
+    }
+}
+
+/// Counts of the open file descriptors of a process, grouped by type
+///
+/// Each descriptor is classified by the target of its `/proc/[pid]/fd` link.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct FdSummary {
+    /// Paths in the filesystem, including directories and devices
+    pub files: usize,
+
+    /// Sockets, shown as `socket:[inode]`
+    pub sockets: usize,
+
+    /// Pipes, shown as `pipe:[inode]`
+    pub pipes: usize,
+
+    /// Event file descriptors, shown as `anon_inode:[eventfd]`
+    pub eventfds: usize,
+
+    /// Epoll instances, shown as `anon_inode:[eventpoll]`
+    pub epolls: usize,
+
+    /// Inotify instances, shown as `anon_inode:inotify`
+    pub inotifies: usize,
+
+    /// Other anonymous inodes, such as timerfds, signalfds and pidfds
+    pub anon_inodes: usize,
+
+    /// Anything else, such as `net:[inode]` namespace descriptors
+    pub other: usize
+}
+
+impl FdSummary {
+    /// Return the number of file descriptors counted.
+    pub fn total(&self) -> usize {
+        self.files + self.sockets + self.pipes + self.eventfds + self.epolls
+            + self.inotifies + self.anon_inodes + self.other
+    }
+
+    /// Count a file descriptor by the target of its link
+    fn add(&mut self, target: &Path) {
+        let target = target.to_string_lossy();
+
+        let count = if target.starts_with('/') {
+            &mut self.files
+        } else if target.starts_with("socket:") {
+            &mut self.sockets
+        } else if target.starts_with("pipe:") {
+            &mut self.pipes
+        } else if target == "anon_inode:[eventfd]" {
+            &mut self.eventfds
+        } else if target == "anon_inode:[eventpoll]" {
+            &mut self.epolls
+        } else if target == "anon_inode:inotify" {
+            &mut self.inotifies
+        } else if target.starts_with("anon_inode:") {
+            &mut self.anon_inodes
+        } else {
+            &mut self.other
+        };
+
+        *count += 1;
+    }
+}
+
+/// Memory usage and limits of the cgroup a process belongs to
+///
+/// For a process in a container these are the limits actually enforced on
+/// it, which the host-wide figures in `/proc/meminfo` don't reflect. All
+/// values are in bytes, and limits are `None` when the cgroup is unlimited.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct CgroupMemory {
+    /// Memory currently charged to the cgroup
+    ///
+    /// Read from `memory.current`, or `memory.usage_in_bytes` on cgroup v1.
+    pub current: u64,
+
+    /// Hard limit, above which the OOM killer is invoked
+    ///
+    /// Read from `memory.max`, or `memory.limit_in_bytes` on cgroup v1.
+    pub max: Option<u64>,
+
+    /// Throttling limit, above which reclaim is forced
+    ///
+    /// Read from `memory.high`, and always `None` on cgroup v1.
+    pub high: Option<u64>,
+
+    /// Swap currently used by the cgroup, if swap accounting is enabled
+    ///
+    /// Read from `memory.swap.current`, or the difference between
+    /// `memory.memsw.usage_in_bytes` and `memory.usage_in_bytes` on cgroup v1.
+    pub swap_current: Option<u64>
+}
+
+/// CPU usage and bandwidth throttling of the cgroup a process belongs to
+///
+/// Throttling happens when a cgroup uses up its CPU quota for a period, and
+/// is a common cause of latency in containers that doesn't show up in the
+/// CPU times of any process. The throttling fields are zero if the `cpu`
+/// controller isn't enabled for the cgroup.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct CgroupCpuStat {
+    /// Total CPU time used by the cgroup, in microseconds
+    pub usage_usec: u64,
+
+    /// Number of enforcement periods that have elapsed
+    pub nr_periods: u64,
+
+    /// Number of periods in which the cgroup was throttled
+    pub nr_throttled: u64,
+
+    /// Total time the cgroup was throttled for, in microseconds
+    pub throttled_usec: u64
+}
+
+impl FromStr for CgroupCpuStat {
+    type Err = Error;
+
+    /// Parse a cgroup v2 `cpu.stat` file
+    fn from_str(contents: &str) -> Result<Self> {
+        let fields = try!(parse_cgroup_keyed(contents, "cpu.stat"));
+        let field = |key: &str| fields.get(key).cloned().unwrap_or(0);
+
+        if !fields.contains_key("usage_usec") {
+            return Err(Error::new(ErrorKind::Other, "Missing usage_usec in cpu.stat"));
+        }
+
+        return Ok(CgroupCpuStat {
+            usage_usec:     field("usage_usec"),
+            nr_periods:     field("nr_periods"),
+            nr_throttled:   field("nr_throttled"),
+            throttled_usec: field("throttled_usec")
+        });
+    }
+}
+
+/// Parse a cgroup file made of `key value` lines, such as `cpu.stat`
+fn parse_cgroup_keyed<'a>(contents: &'a str, name: &str) -> Result<HashMap<&'a str, u64>> {
+    let mut fields = HashMap::new();
+
+    for line in contents.lines() {
+        let mut parts = line.split_whitespace();
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => continue
+        };
+
+        fields.insert(key, try!(value.parse().map_err(|_| {
+            Error::new(ErrorKind::Other, format!("Could not parse {} in {}", key, name))
+        })));
+    }
+
+    return Ok(fields);
+}
+
+/// Find the directory of the cgroup a process belongs to
+///
+/// With no controller this is the cgroup v2 directory, and otherwise it is the
+/// cgroup v1 hierarchy the controller is mounted on. Paths in
+/// `/proc/[pid]/cgroup` are resolved against the mounts of this process, as
+/// that is where the directory will be read from. Returns `Ok(None)` if the
+/// hierarchy isn't mounted, or the cgroup isn't visible in this namespace.
+fn cgroup_dir(pid: PID, controller: Option<&str>) -> Result<Option<PathBuf>> {
+    let cgroups = try!(procfs(pid, "cgroup"));
+    let mounts: Vec<MountInfo> = try!(try!(read_file(Path::new("/proc/self/mountinfo")))
+        .lines().map(FromStr::from_str).collect());
+
+    for line in cgroups.lines() {
+        // Each line is `hierarchy-ID:controller-list:cgroup-path`
+        let fields: Vec<&str> = line.splitn(3, ':').collect();
+        if fields.len() != 3 {
+            continue;
+        }
+
+        let matches = match controller {
+            None => fields[0] == "0" && fields[1].is_empty(),
+            Some(name) => fields[1].split(',').any(|c| c == name)
+        };
+        if !matches {
+            continue;
+        }
+
+        let path = Path::new(fields[2]);
+        for mount in mounts.iter().rev() {
+            let mounted = match controller {
+                None => mount.fs_type == "cgroup2",
+                Some(name) => mount.fs_type == "cgroup"
+                    && mount.super_options.split(',').any(|o| o == name)
+            };
+            if !mounted {
+                continue;
+            }
+
+            if let Ok(relative) = path.strip_prefix(&mount.root) {
+                let dir = mount.mount_point.join(relative);
+                if dir.is_dir() {
+                    return Ok(Some(dir));
+                }
+            }
+        }
+    }
+
+    return Ok(None);
+}
+
+/// Read a numeric file from a cgroup directory, where `max` means unlimited
+///
+/// Returns `Ok(None)` if the file doesn't exist.
+fn read_cgroup_value(dir: &Path, name: &str) -> Result<Option<u64>> {
+    let contents = match read_file(&dir.join(name)) {
+        Ok(contents) => contents,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e)
+    };
+
+    match contents.trim() {
+        "max" => Ok(Some(u64::max_value())),
+        value => value.parse().map(Some).map_err(|_| Error::new(ErrorKind::Other,
+            format!("Could not parse {}", dir.join(name).display())))
+    }
+}
+
+/// Convert a cgroup limit to `None` if it is unlimited
+///
+/// Cgroup v2 writes `max` for no limit, while cgroup v1 reports the largest
+/// page-aligned value that fits in an `i64`.
+fn cgroup_limit(limit: Option<u64>) -> Option<u64> {
+    limit.and_then(|limit| {
+        if limit >= (i64::max_value() as u64) - page_size() { None } else { Some(limit) }
+    })
+}
+
+/// Real, effective, saved set and filesystem IDs of a process
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct Ids {
+    pub real: u32,
+    pub effective: u32,
+    pub saved: u32,
+    pub filesystem: u32
+}
+
+impl FromStr for Ids {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let ids: Vec<u32> = try!(s.split_whitespace()
+            .map(|id| id.parse().map_err(|_| {
+                Error::new(ErrorKind::Other, "Could not parse IDs from /proc/[pid]/status")
+            }))
+            .collect());
+
+        if ids.len() != 4 {
+            return Err(Error::new(ErrorKind::Other,
+                "Expected four IDs in /proc/[pid]/status"));
+        }
+
+        return Ok(Ids { real: ids[0], effective: ids[1], saved: ids[2], filesystem: ids[3] });
+    }
+}
+
+/// Information about a process gathered from `/proc/[pid]/status`.
+///
+/// Memory sizes are converted to bytes. They are `None` for kernel threads,
+/// which have no memory of their own.
+#[derive(Clone,Debug)]
+pub struct Status {
+    /// Filename of the executable
+    pub name: String,
+
+    /// State of the process as an enum
+    pub state: State,
+
+    /// Thread group ID, which is the PID of the process a thread belongs to
+    pub tgid: PID,
+
+    /// PID of the process (or thread ID, for a thread)
+    pub pid: PID,
+
+    /// PID of the parent process
+    pub ppid: PID,
+
+    /// PID of the process tracing this process, or 0
+    pub tracer_pid: PID,
+
+    /// User IDs of the process
+    pub uid: Ids,
+
+    /// Group IDs of the process
+    pub gid: Ids,
+
+    /// Peak virtual memory size (bytes)
+    pub vm_peak: Option<u64>,
+
+    /// Virtual memory size (bytes)
+    pub vm_size: Option<u64>,
+
+    /// Locked memory size (bytes)
+    pub vm_lck: Option<u64>,
+
+    /// Peak resident set size (bytes)
+    pub vm_hwm: Option<u64>,
+
+    /// Resident set size (bytes)
+    pub vm_rss: Option<u64>,
+
+    /// Size of the data segment (bytes)
+    pub vm_data: Option<u64>,
+
+    /// Size of the stack (bytes)
+    pub vm_stk: Option<u64>,
+
+    /// Size of the text segment (bytes)
+    pub vm_exe: Option<u64>,
+
+    /// Size of shared library code (bytes)
+    pub vm_lib: Option<u64>,
+
+    /// Size of the page tables (bytes)
+    pub vm_pte: Option<u64>,
+
+    /// Swapped out virtual memory size (bytes)
+    pub vm_swap: Option<u64>,
+
+    /// Number of threads in the process
+    pub threads: u64,
+
+    /// Mask of signals pending for the thread
+    pub sig_pnd: Option<u64>,
+
+    /// Mask of signals pending for the process
+    pub shd_pnd: Option<u64>,
+
+    /// Mask of blocked signals
+    pub sig_blk: Option<u64>,
+
+    /// Mask of ignored signals
+    pub sig_ign: Option<u64>,
+
+    /// Mask of caught signals
+    pub sig_cgt: Option<u64>,
+
+    /// Mask of inheritable capabilities
+    pub cap_inh: Option<u64>,
+
+    /// Mask of permitted capabilities
+    pub cap_prm: Option<u64>,
+
+    /// Mask of effective capabilities
+    pub cap_eff: Option<u64>,
+
+    /// Mask of the capability bounding set
+    pub cap_bnd: Option<u64>,
+
+    /// Mask of ambient capabilities (since Linux 4.3)
+    pub cap_amb: Option<u64>,
+
+    /// Seccomp mode, 0 (disabled), 1 (strict) or 2 (filter)
+    pub seccomp: Option<u32>,
+
+    /// Number of seccomp filters attached (since Linux 5.9)
+    pub seccomp_filters: Option<u32>,
+
+    /// Whether the `no_new_privs` bit is set (since Linux 4.10)
+    pub no_new_privs: Option<bool>,
+
+    /// Number of voluntary context switches
+    pub voluntary_ctxt_switches: u64,
+
+    /// Number of involuntary context switches
+    pub nonvoluntary_ctxt_switches: u64
+}
+
+impl Status {
+    fn new(pid: PID) -> Result<Status> {
+        procfs_with(pid, "status", FromStr::from_str)
+    }
+}
+
+/// Parse a `/proc/[pid]/status` value, erroring if it is missing
+fn status_field<T: FromStr>(fields: &HashMap<&str, &str>, key: &str) -> Result<T> {
+    match fields.get(key) {
+        Some(value) => value.parse().map_err(|_| Error::new(ErrorKind::Other,
+            format!("Could not parse {} from /proc/[pid]/status", key))),
+        None => Err(Error::new(ErrorKind::Other,
+            format!("Missing {} in /proc/[pid]/status", key)))
+    }
+}
+
+/// Parse an optional `/proc/[pid]/status` value
+fn status_option<T: FromStr>(fields: &HashMap<&str, &str>, key: &str) -> Result<Option<T>> {
+    match fields.get(key) {
+        Some(_) => status_field(fields, key).map(Some),
+        None => Ok(None)
+    }
+}
+
+/// Parse an optional `/proc/[pid]/status` flag shown as 0 or 1
+fn status_flag(fields: &HashMap<&str, &str>, key: &str) -> Result<Option<bool>> {
+    Ok(try!(status_option::<u8>(fields, key)).map(|flag| flag != 0))
+}
+
+/// Parse a `/proc/[pid]/status` hexadecimal mask
+fn status_mask(fields: &HashMap<&str, &str>, key: &str) -> Result<Option<u64>> {
+    match fields.get(key) {
+        Some(value) => match u64::from_str_radix(value, 16) {
+            Ok(mask) => Ok(Some(mask)),
+            Err(_) => Err(Error::new(ErrorKind::Other,
+                format!("Could not parse {} from /proc/[pid]/status", key)))
+        },
+        None => Ok(None)
+    }
+}
+
+/// Parse a `/proc/[pid]/status` memory size in kB as bytes
+fn status_size(fields: &HashMap<&str, &str>, key: &str) -> Result<Option<u64>> {
+    match fields.get(key) {
+        Some(value) => match value.trim_right_matches("kB").trim().parse::<u64>() {
+            Ok(kb) => Ok(Some(kb * 1024)),
+            Err(_) => Err(Error::new(ErrorKind::Other,
+                format!("Could not parse {} from /proc/[pid]/status", key)))
+        },
+        None => Ok(None)
+    }
+}
+
+impl FromStr for Status {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for line in s.lines() {
+            let mut parts = line.splitn(2, ':');
+            if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                fields.insert(key, value.trim());
+            }
+        }
+
+        // The state is shown as e.g. "S (sleeping)"
+        let state = try!(fields.get("State").and_then(|s| s.chars().next()).ok_or(
+            Error::new(ErrorKind::Other, "Missing State in /proc/[pid]/status")));
+
+        return Ok(Status {
+            name:                       try!(status_field(&fields, "Name")),
+            state:                      try!(State::from_char(state)),
+            tgid:                       try!(status_field(&fields, "Tgid")),
+            pid:                        try!(status_field(&fields, "Pid")),
+            ppid:                       try!(status_field(&fields, "PPid")),
+            tracer_pid:                 try!(status_field(&fields, "TracerPid")),
+            uid:                        try!(status_field(&fields, "Uid")),
+            gid:                        try!(status_field(&fields, "Gid")),
+            vm_peak:                    try!(status_size(&fields, "VmPeak")),
+            vm_size:                    try!(status_size(&fields, "VmSize")),
+            vm_lck:                     try!(status_size(&fields, "VmLck")),
+            vm_hwm:                     try!(status_size(&fields, "VmHWM")),
+            vm_rss:                     try!(status_size(&fields, "VmRSS")),
+            vm_data:                    try!(status_size(&fields, "VmData")),
+            vm_stk:                     try!(status_size(&fields, "VmStk")),
+            vm_exe:                     try!(status_size(&fields, "VmExe")),
+            vm_lib:                     try!(status_size(&fields, "VmLib")),
+            vm_pte:                     try!(status_size(&fields, "VmPTE")),
+            vm_swap:                    try!(status_size(&fields, "VmSwap")),
+            threads:                    try!(status_field(&fields, "Threads")),
+            sig_pnd:                    try!(status_mask(&fields, "SigPnd")),
+            shd_pnd:                    try!(status_mask(&fields, "ShdPnd")),
+            sig_blk:                    try!(status_mask(&fields, "SigBlk")),
+            sig_ign:                    try!(status_mask(&fields, "SigIgn")),
+            sig_cgt:                    try!(status_mask(&fields, "SigCgt")),
+            cap_inh:                    try!(status_mask(&fields, "CapInh")),
+            cap_prm:                    try!(status_mask(&fields, "CapPrm")),
+            cap_eff:                    try!(status_mask(&fields, "CapEff")),
+            cap_bnd:                    try!(status_mask(&fields, "CapBnd")),
+            cap_amb:                    try!(status_mask(&fields, "CapAmb")),
+            seccomp:                    try!(status_option(&fields, "Seccomp")),
+            seccomp_filters:            try!(status_option(&fields, "Seccomp_filters")),
+            no_new_privs:               try!(status_flag(&fields, "NoNewPrivs")),
+            voluntary_ctxt_switches:    try!(status_field(&fields, "voluntary_ctxt_switches")),
+            nonvoluntary_ctxt_switches: try!(status_field(&fields, "nonvoluntary_ctxt_switches"))
+        });
+    }
+}
+
+/// CPU time a process has been scheduled for, in clock ticks
+///
+/// `Process::utime` and `Process::stime` are converted to seconds as `f64`,
+/// which can no longer represent a single tick once a process has used a few
+/// million years of CPU time at 100 ticks per second, and loses precision in
+/// differences between samples long before that. These are the counters as
+/// the kernel reports them, so differences between two samples are exact.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct CpuTimes {
+    /// Clock ticks scheduled in user mode
+    pub user: u64,
+
+    /// Clock ticks scheduled in kernel mode
+    pub system: u64,
+
+    /// Clock ticks waited-for children were scheduled in user mode
+    pub children_user: u64,
+
+    /// Clock ticks waited-for children were scheduled in kernel mode
+    pub children_system: u64,
+
+    /// Number of clock ticks in a second
+    pub ticks_per_second: u64
+}
+
+/// Percentages of a CPU a process used in each mode over an interval
+///
+/// Returned by `Process::cpu_times_percent`. A high `system_percent` points
+/// at a workload that spends its time in system calls.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct CpuTimesPercent {
+    /// Percentage of a CPU used in user mode
+    pub user_percent: f64,
+
+    /// Percentage of a CPU used in kernel mode
+    pub system_percent: f64,
+
+    /// Percentage of a CPU used in user mode by children that were waited for
+    pub children_user_percent: f64,
+
+    /// Percentage of a CPU used in kernel mode by children that were waited for
+    pub children_system_percent: f64
+}
+
+impl CpuTimes {
+    /// Return the ticks scheduled in both user and kernel mode.
+    pub fn total(&self) -> u64 {
+        self.user.saturating_add(self.system)
+    }
+
+    /// Return the ticks scheduled since an earlier sample of the same process.
+    ///
+    /// The kernel's counters only increase, so this is zero if `earlier` is
+    /// actually the later sample.
+    pub fn ticks_since(&self, earlier: &CpuTimes) -> u64 {
+        self.total().saturating_sub(earlier.total())
+    }
+
+    /// Return the percentage of a CPU used since an earlier sample.
+    ///
+    /// `elapsed` is the wall-clock time between the samples. A process with
+    /// several threads can use more than 100% of a CPU.
+    pub fn percent_since(&self, earlier: &CpuTimes, elapsed: Duration) -> f64 {
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+        if seconds <= 0.0 || self.ticks_per_second == 0 {
+            return 0.0;
+        }
+
+        let used = self.ticks_since(earlier) as f64 / self.ticks_per_second as f64;
+        return used / seconds * 100.0;
+    }
+
+    /// Return the percentage of a CPU used in each mode since an earlier sample.
+    ///
+    /// The children's times only include children that were waited for, so
+    /// they increase when a child is reaped rather than while it runs.
+    pub fn percent_breakdown_since(&self, earlier: &CpuTimes, elapsed: Duration) -> CpuTimesPercent {
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+        if seconds <= 0.0 || self.ticks_per_second == 0 {
+            return CpuTimesPercent::default();
+        }
+
+        let percent = |after: u64, before: u64| {
+            after.saturating_sub(before) as f64 / self.ticks_per_second as f64 / seconds * 100.0
+        };
+
+        return CpuTimesPercent {
+            user_percent: percent(self.user, earlier.user),
+            system_percent: percent(self.system, earlier.system),
+            children_user_percent: percent(self.children_user, earlier.children_user),
+            children_system_percent: percent(self.children_system, earlier.children_system)
+        };
+    }
+}
+
+/// Information about a process gathered from `/proc/[pid]/stat`.
+///
+/// **IMPORTANT**: See the module level notes for information on the types used
+/// by this struct, as some do not match those used by `/proc/[pid]/stat`.
+#[derive(Clone,Debug)]
+pub struct Process {
+    /// PID of the process
+    pub pid: PID,
+
+    /// UID of the process
+    pub uid: UID,
+
+    /// UID of the process
+    pub gid: GID,
+
+    /// Filename of the executable
+    ///
+    /// Any process can set its name to bytes that aren't valid UTF-8, which
+    /// are replaced with `U+FFFD` rather than failing to read the process.
+    pub comm: String,
+
+    /// State of the process as an enum
+    pub state: State,
+
+    /// PID of the parent process
+    pub ppid: PID,
+
+    /// Process group ID
+    pub pgrp: i32,
+
+    /// Session ID
+    pub session: i32,
+
+    /// Controlling terminal of the process [TODO: Actually two numbers]
+    pub tty_nr: i32,
+
+    /// ID of the foreground group of the controlling terminal
+    pub tpgid: i32,
+
+    /// Kernel flags for the process
+    pub flags: u32,
+
+    /// Minor faults
+    pub minflt: u64,
+
+    /// Minor faults by child processes
+    pub cminflt: u64,
+
+    /// Major faults
+    pub majflt: u64,
+
+    /// Major faults by child processes
+    pub cmajflt: u64,
+
+    /// Time scheduled in user mode (seconds)
+    pub utime: f64,
+
+    /// Time scheduled in kernel mode (seconds)
+    pub stime: f64,
+
+    /// Time waited-for child processes were scheduled in user mode (seconds)
+    pub cutime: f64,
+
+    /// Time waited-for child processes were scheduled in kernel mode (seconds)
+    pub cstime: f64,
+
+    /// Priority value (-100..-2 | 0..39)
+    pub priority: i64,
+
+    /// Nice value (-20..19)
+    pub nice: i64,
+
+    /// Number of threads in the process
+    pub num_threads: i64,
+
+    // /// Unmaintained field since linux 2.6.17, always 0
+    // itrealvalue: i64,
+
+    /// Time the process was started after system boot (clock ticks)
+    pub starttime: u64,
+
+    /// Virtual memory size in bytes
+    pub vsize: u64,
+
+    /// Resident Set Size (pages) [TODO: Calculate size in bytes]
+    pub rss: i64,
+
+    /// Current soft limit on process RSS (bytes)
+    pub rsslim: u64,
+
+    // These values are memory addresses, returned by the getters of the same name
+    startcode: u64,
+    endcode: u64,
+    startstack: u64,
+    kstkesp: u64,
+    kstkeip: u64,
+
+    // /// Signal bitmaps
+    // /// These are obselete, use `/proc/[pid]/status` instead
+    // signal: u64,
+    // blocked: u64,
+    // sigignore: u64,
+    // sigcatch: u64,
+
+    /// Channel the process is waiting on (address of a system call)
+    pub wchan: u64,
+
+    // /// Number of pages swapped (not maintained)
+    // pub nswap: u64,
+
+    // /// Number of pages swapped for child processes (not maintained)
+    // pub cnswap: u64,
+
+    /// Signal sent to parent when process dies
+    pub exit_signal: i32,
+
+    /// Number of the CPU the process was last executed on
+    pub processor: i32,
+
+    /// Real-time scheduling priority (0 | 1..99)
+    pub rt_priority: u32,
+
+    /// Scheduling policy
+    pub policy: u32,
+
+    /// Aggregated block I/O delays (clock ticks)
+    pub delayacct_blkio_ticks: u64,
+
+    /// Guest time of the process (seconds)
+    pub guest_time: f64,
+
+    /// Guest time of the process's children (seconds)
+    pub cguest_time: f64,
+
+    // More memory addresses, also returned by getters
+    start_data: u64,
+    end_data: u64,
+    start_brk: u64,
+    arg_start: u64,
+    arg_end: u64,
+    env_start: u64,
+    env_end: u64,
+
+    /// The thread's exit status
+    pub exit_code: i32,
+
+    // Raw CPU times, returned by `cpu_times`
+    cpu_ticks: CpuTimes,
+
+    // Memoized by `status_cached`
+    status: Option<Status>,
+
+    // Whether mutating methods call `check_identity` first
+    identity_check: bool
+}
+
+/// A thread of a process
+///
+/// Read from `/proc/[pid]/task/[tid]/stat`, which has the same fields as
+/// `/proc/[pid]/stat` but only describes one thread.
+#[derive(Clone,Debug)]
+pub struct Thread {
+    /// PID of the process the thread belongs to
+    pub pid: PID,
+
+    /// Thread ID, which is the same as `pid` for the main thread
+    pub tid: PID,
+
+    /// Name of the thread, which starts as the name of the executable
+    pub comm: String,
+
+    /// State of the thread
+    pub state: State,
+
+    /// Time scheduled in user mode (seconds)
+    pub utime: f64,
+
+    /// Time scheduled in kernel mode (seconds)
+    pub stime: f64,
+
+    /// Number of the CPU the thread was last executed on
+    pub processor: i32,
+
+    // Raw CPU times, returned by `cpu_times`
+    cpu_ticks: CpuTimes
+}
+
+impl Thread {
+    /// Read a thread from `/proc/[pid]/task/[tid]/stat`.
+    pub fn new(pid: PID, tid: PID) -> Result<Thread> {
+        let path = procfs_path(pid, &format!("task/{}/stat", tid));
+        let meta = try!(fs::metadata(&path).map_err(|e| Error::for_process(tid, e)));
+        let stat = try!(read_file_bytes(&path).map_err(|e| Error::for_process(tid, e)));
+        let stat = try!(Process::from_stat(&String::from_utf8_lossy(&stat),
+            meta.uid(), meta.gid(), ticks_per_second()));
+
+        return Ok(Thread {
+            pid: pid,
+            tid: stat.pid,
+            comm: stat.comm,
+            state: stat.state,
+            utime: stat.utime,
+            stime: stat.stime,
+            processor: stat.processor,
+            cpu_ticks: stat.cpu_ticks
+        });
+    }
+
+    /// Return the CPU time the thread had been scheduled for in clock ticks.
+    pub fn cpu_times(&self) -> CpuTimes {
+        self.cpu_ticks
+    }
+
+    /// Read the current name of the thread from `/proc/[pid]/task/[tid]/comm`.
+    ///
+    /// Unlike `comm`, this reflects names set after the thread was read.
+    pub fn name(&self) -> Result<String> {
+        let comm = try!(procfs(self.pid, &format!("task/{}/comm", self.tid)));
+        Ok(comm.trim_right_matches('\n').to_string())
+    }
+
+    /// Return the CPUs the thread is allowed to run on.
+    pub fn get_cpu_affinity(&self) -> Result<Vec<usize>> {
+        get_affinity(self.tid).map(|cpus| cpus.iter().collect())
+    }
+
+    /// Restrict the thread to running on the given CPUs.
+    ///
+    /// See `Process::set_cpu_affinity`, which this behaves the same as.
+    pub fn set_cpu_affinity(&self, cpus: &[usize]) -> Result<()> {
+        set_affinity(self.tid, &CpuSet::from(cpus))
+    }
+}
+
+/// Number of CPUs in the first mask tried by `get_affinity`
+const AFFINITY_MIN_CPUS: usize = 1024;
+
+/// A set of CPUs, stored as a bitmask in the same way as `cpu_set_t`
+///
+/// This is more compact than a list of CPU numbers on machines with many
+/// CPUs, and makes it simple to combine affinities before applying them.
+#[derive(Clone,Debug,Default,PartialEq,Eq,Hash)]
+pub struct CpuSet {
+    // Bit `n % 64` of word `n / 64` is set for CPU `n`, with no trailing
+    // zero words so that equal sets compare equal
+    words: Vec<u64>
+}
+
+impl CpuSet {
+    /// Return an empty set.
+    pub fn new() -> CpuSet {
+        CpuSet { words: Vec::new() }
+    }
+
+    /// Create a set from a mask in the format used by the kernel
+    fn from_words(mut words: Vec<u64>) -> CpuSet {
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+        return CpuSet { words: words };
+    }
+
+    /// Return `true` if `cpu` is in the set.
+    pub fn contains(&self, cpu: usize) -> bool {
+        self.words.get(cpu / 64).map_or(false, |word| word & (1 << (cpu % 64)) != 0)
+    }
+
+    /// Add `cpu` to the set.
+    pub fn set(&mut self, cpu: usize) {
+        if self.words.len() <= cpu / 64 {
+            self.words.resize(cpu / 64 + 1, 0);
+        }
+        self.words[cpu / 64] |= 1 << (cpu % 64);
+    }
+
+    /// Remove `cpu` from the set.
+    pub fn clear(&mut self, cpu: usize) {
+        if let Some(word) = self.words.get_mut(cpu / 64) {
+            *word &= !(1 << (cpu % 64));
+        }
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    /// Return the number of CPUs in the set.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Return `true` if there are no CPUs in the set.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Return the CPUs in either set.
+    pub fn union(&self, other: &CpuSet) -> CpuSet {
+        let len = max(self.words.len(), other.words.len());
+        CpuSet::from_words((0..len).map(|i| {
+            self.words.get(i).cloned().unwrap_or(0) | other.words.get(i).cloned().unwrap_or(0)
+        }).collect())
+    }
+
+    /// Return the CPUs in both sets.
+    pub fn intersection(&self, other: &CpuSet) -> CpuSet {
+        CpuSet::from_words(self.words.iter().zip(other.words.iter()).map(|(a, b)| a & b).collect())
+    }
+
+    /// Iterate over the CPUs in the set in ascending order.
+    pub fn iter(&self) -> CpuSetIter<'_> {
+        CpuSetIter { set: self, cpu: 0 }
+    }
+}
+
+impl<'a> From<&'a [usize]> for CpuSet {
+    fn from(cpus: &'a [usize]) -> CpuSet {
+        let mut set = CpuSet::new();
+        for cpu in cpus {
+            set.set(*cpu);
+        }
+        return set;
+    }
+}
+
+/// Iterator over the CPUs in a `CpuSet`, returned by `CpuSet::iter`
+pub struct CpuSetIter<'a> {
+    set: &'a CpuSet,
+    cpu: usize
+}
+
+impl<'a> Iterator for CpuSetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.cpu < self.set.words.len() * 64 {
+            let cpu = self.cpu;
+            self.cpu += 1;
+            if self.set.contains(cpu) {
+                return Some(cpu);
+            }
+        }
+        return None;
+    }
+}
+
+/// Read the CPU affinity of a thread with `sched_getaffinity(2)`
+///
+/// The size of the kernel's mask isn't known, so the mask is grown until the
+/// kernel accepts it.
+fn get_affinity(tid: PID) -> Result<CpuSet> {
+    let mut words = AFFINITY_MIN_CPUS / 64;
+
+    loop {
+        let mut mask: Vec<u64> = vec![0; words];
+        let code = unsafe { sched_getaffinity(tid, (words * 8) as size_t, mask.as_mut_ptr()) };
+
+        if code == 0 {
+            return Ok(CpuSet::from_words(mask));
+        }
+
+        match Error::last_os_error() {
+            ref e if e.raw_os_error() == Some(EINVAL) && words < 1 << 16 => words *= 2,
+            e => return Err(Error::for_process(tid, e))
+        }
+    }
+}
+
+/// Set the CPU affinity of a thread with `sched_setaffinity(2)`
+fn set_affinity(tid: PID, cpus: &CpuSet) -> Result<()> {
+    if cpus.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "No CPUs given to set the affinity to"));
+    }
+
+    // CPUs beyond the end of the mask are taken to be cleared
+    let mask = &cpus.words;
+    let words = mask.len();
+
+    return match unsafe { sched_setaffinity(tid, (words * 8) as size_t, mask.as_ptr()) } {
+        0 => Ok(()),
+        _ => match Error::last_os_error() {
+            ref e if e.raw_os_error() == Some(EPERM) => {
+                Err(Error::new(ErrorKind::PermissionDenied, format!(
+                    "Setting the CPU affinity of {} requires CAP_SYS_NICE", tid)))
+            },
+            e => Err(Error::for_process(tid, e))
+        }
+    };
+}
+
+/// Error for a process whose PID now belongs to a different process
+fn pid_reused(pid: PID) -> Error {
+    Error::NoSuchProcess { pid: pid }
+}
+
+/// Names of the fields of `/proc/[pid]/stat`, as used in `proc(5)`
+const STAT_FIELDS: [&'static str; 52] = [
+    "pid",
+    "comm",
+    "state",
+    "ppid",
+    "pgrp",
+    "session",
+    "tty_nr",
+    "tpgid",
+    "flags",
+    "minflt",
+    "cminflt",
+    "majflt",
+    "cmajflt",
+    "utime",
+    "stime",
+    "cutime",
+    "cstime",
+    "priority",
+    "nice",
+    "num_threads",
+    "itrealvalue",
+    "starttime",
+    "vsize",
+    "rss",
+    "rsslim",
+    "startcode",
+    "endcode",
+    "startstack",
+    "kstkesp",
+    "kstkeip",
+    "signal",
+    "blocked",
+    "sigignore",
+    "sigcatch",
+    "wchan",
+    "nswap",
+    "cnswap",
+    "exit_signal",
+    "processor",
+    "rt_priority",
+    "policy",
+    "delayacct_blkio_ticks",
+    "guest_time",
+    "cguest_time",
+    "start_data",
+    "end_data",
+    "start_brk",
+    "arg_start",
+    "arg_end",
+    "env_start",
+    "env_end",
+    "exit_code"
+];
+
+/// Describe each field of `/proc/[pid]/stat` on its own line, for errors
+///
+/// Each line has the field number, its name, the raw token and how it parses.
+/// Fields are shown even after one that fails, as a missing or extra field
+/// usually shifts every field after it.
+fn describe_stat(stat: &str) -> String {
+    let fields = match Process::split_stat(stat) {
+        Ok(fields) => fields,
+        Err(_) => return format!("  unsplittable contents: {:?}", stat)
+    };
+
+    let mut description = String::new();
+    for i in 0..max(fields.len(), STAT_FIELDS.len()) {
+        let name = STAT_FIELDS.get(i).cloned().unwrap_or("(unknown)");
+        let parsed = match (i, fields.get(i)) {
+            (_, None) => "<missing>".to_string(),
+            (1, Some(_)) => "string".to_string(),
+            (2, Some(token)) => match State::from_str(token) {
+                Ok(state) => format!("{:?}", state),
+                Err(_) => "could not parse as a state".to_string()
+            },
+            (_, Some(token)) => match (i64::from_str(token), u64::from_str(token)) {
+                (Ok(n), _) => n.to_string(),
+                (_, Ok(n)) => n.to_string(),
+                _ => "could not parse as an integer".to_string()
+            }
+        };
+
+        description.push_str(&format!("\n  {:2} {:<22} {:<22} {}",
+            i + 1, name, format!("{:?}", fields.get(i).cloned().unwrap_or("")), parsed));
+    }
+
+    return description;
+}
+
+macro_rules! from_str { ($field:expr) => (try!(FromStr::from_str($field).map_err(|_| {
+    Error::new(ErrorKind::Other, "Could not parse field from /proc/[pid]/stat")
+}))) }
+
+impl Process {
+    /// Splits the contents of `/proc/[pid]/stat` into fields.
+    ///
+    /// The process name is surrounded by `()` characters, which are removed.
+    /// As the name can itself contain spaces and `)` characters, it is taken
+    /// to be everything between the first `(` and the last `)` in the file,
+    /// and only the fields after that are split on whitespace.
+    fn split_stat(stat: &str) -> Result<Vec<&str>> {
+        let (start, end) = match (stat.find('('), stat.rfind(')')) {
+            (Some(start), Some(end)) if start < end => (start, end),
+            _ => return Err(Error::new(ErrorKind::Other,
+                "Could not find process name in /proc/[pid]/stat"))
+        };
+
+        let mut fields = Vec::with_capacity(52);
+        fields.push(stat[..start].trim());
+        fields.push(&stat[start+1..end]);
+        fields.extend(stat[end+1..].split_whitespace());
+        return Ok(fields);
+    }
+
+    /// Attempts to read process information from `/proc/[pid]/stat`.
+    ///
+    /// `/stat` is seperated by spaces and contains a trailing newline.
+    ///
+    /// This should return a psutil/process specific error type, so that  errors
+    /// can be raised by `FromStr` too
+    ///
+    /// `pid` can also be the ID of a thread other than the main thread of a
+    /// process, in which case the result describes that thread. Use
+    /// `is_thread_group_leader` to check for this, and `thread_group_leader`
+    /// to read the process it belongs to.
+    pub fn new(pid: PID) -> Result<Process> {
+        let meta = try!(fs::metadata(procfs_path(pid, "stat")).map_err(|e| Error::for_process(pid, e)));
+
+        procfs_with_bytes(pid, "stat", |stat| {
+            Process::from_stat(&String::from_utf8_lossy(stat), meta.uid(), meta.gid(), ticks_per_second())
+        })
+    }
+
+    /// Parses the contents of `/proc/[pid]/stat`.
+    ///
+    /// The owner of the process isn't included in `/proc/[pid]/stat`, and
+    /// `Process::new` takes it from the owner of the file. CPU times are
+    /// converted to seconds using `ticks_per_second`, which `Process::new`
+    /// reads from `sysconf(_SC_CLK_TCK)`.
+    pub fn from_stat(stat: &str, uid: UID, gid: GID, ticks_per_second: u64) -> Result<Process> {
+        let stat = try!(Process::split_stat(stat));
+
+        // This may only be the case for Linux, but this can be removed or
+        // changed when/if support for other kernels is needed
+        if stat.len() < 52 {
+            return Err(Error::new(ErrorKind::Other,
+                "Unexpected number of fields from /proc/[pid]/stat"));
+        }
+
+        // The children's times are printed as signed, but are never negative
+        let cutime: i64 = from_str!(stat[15]);
+        let cstime: i64 = from_str!(stat[16]);
+        let guest_time: u64 = from_str!(stat[42]);
+        let cguest_time: i64 = from_str!(stat[43]);
+        let cpu_ticks = CpuTimes {
+            user: from_str!(stat[13]),
+            system: from_str!(stat[14]),
+            children_user: cutime.max(0) as u64,
+            children_system: cstime.max(0) as u64,
+            ticks_per_second: ticks_per_second
+        };
+        let ticks_per_second = ticks_per_second as f64;
+
+        // Read each field into an attribute for a new Process instance
+        return Ok(Process {
+            pid:                    from_str!(stat[00]),
+            uid:                    uid,
+            gid:                    gid,
+            comm:                   stat[01].to_string(),
+            state:                  from_str!(stat[02]),
+            ppid:                   from_str!(stat[03]),
+            pgrp:                   from_str!(stat[04]),
+            session:                from_str!(stat[05]),
+            tty_nr:                 from_str!(stat[06]),
+            tpgid:                  from_str!(stat[07]),
+            flags:                  from_str!(stat[08]),
+            minflt:                 from_str!(stat[09]),
+            cminflt:                from_str!(stat[10]),
+            majflt:                 from_str!(stat[11]),
+            cmajflt:                from_str!(stat[12]),
+            utime:                  cpu_ticks.user as f64 / ticks_per_second,
+            stime:                  cpu_ticks.system as f64 / ticks_per_second,
+            cutime:                 cutime as f64 / ticks_per_second,
+            cstime:                 cstime as f64 / ticks_per_second,
+            priority:               from_str!(stat[17]),
+            nice:                   from_str!(stat[18]),
+            num_threads:            from_str!(stat[19]),
+            // itrealvalue:         from_str!(stat[20]),
+            starttime:              from_str!(stat[21]),
+            vsize:                  from_str!(stat[22]),
+            rss:                    from_str!(stat[23]),
+            rsslim:                 from_str!(stat[24]),
+            startcode:              from_str!(stat[25]),
+            endcode:                from_str!(stat[26]),
+            startstack:             from_str!(stat[27]),
+            kstkesp:                from_str!(stat[28]),
+            kstkeip:                from_str!(stat[29]),
+            // signal:              from_str!(stat[30]),
+            // blocked:             from_str!(stat[31]),
+            // sigignore:           from_str!(stat[32]),
+            // sigcatch:            from_str!(stat[33]),
+            wchan:                  from_str!(stat[34]),
+            // nswap:               from_str!(stat[35]),
+            // cnswap:              from_str!(stat[36]),
+            exit_signal:            from_str!(stat[37]),
+            processor:              from_str!(stat[38]),
+            rt_priority:            from_str!(stat[39]),
+            policy:                 from_str!(stat[40]),
+            delayacct_blkio_ticks:  from_str!(stat[41]),
+            guest_time:             guest_time as f64 / ticks_per_second,
+            cguest_time:            cguest_time as f64 / ticks_per_second,
+            start_data:             from_str!(stat[44]),
+            end_data:               from_str!(stat[45]),
+            start_brk:              from_str!(stat[46]),
+            arg_start:              from_str!(stat[47]),
+            arg_end:                from_str!(stat[48]),
+            env_start:              from_str!(stat[49]),
+            env_end:                from_str!(stat[50]),
+            exit_code:              from_str!(stat[51]),
+            cpu_ticks:              cpu_ticks,
+            status:                 None,
+            identity_check:         true
+        });
+    }
+
+    /// Read a process like `new`, but with detailed errors for debugging.
+    ///
+    /// If `/proc/[pid]/stat` can't be parsed, the error lists every field with
+    /// its name, its raw token and the value it parses to, which is what is
+    /// needed to report a parsing problem with a particular kernel. See
+    /// `from_stat_verbose`.
+    pub fn new_verbose(pid: PID) -> Result<Process> {
+        let path = procfs_path(pid, "stat");
+        let stat = try!(procfs_bytes(pid, "stat"));
+        let meta = try!(fs::metadata(path).map_err(|e| Error::for_process(pid, e)));
+
+        Process::from_stat_verbose(&String::from_utf8_lossy(&stat), meta.uid(), meta.gid(), ticks_per_second())
+    }
+
+    /// Parse `/proc/[pid]/stat` like `from_stat`, describing each field if it
+    /// fails.
+    ///
+    /// The error has the same kind as from `from_stat`, and its message is
+    /// followed by one line per field.
+    pub fn from_stat_verbose(stat: &str, uid: UID, gid: GID, ticks_per_second: u64) -> Result<Process> {
+        Process::from_stat(stat, uid, gid, ticks_per_second).map_err(|e| {
+            Error::new(e.kind(), format!("{}:{}", e, describe_stat(stat)))
+        })
+    }
+
+    /// Read a process and as much else about it as possible.
+    ///
+    /// Only `/proc/[pid]/stat` is required, and an error reading it is
+    /// returned. Every other file is read even if an earlier one failed, and
+    /// its result is kept in the returned `LenientProcess` - this suits
+    /// monitoring tools that show what they can rather than nothing. See
+    /// `ProcessInfoBuilder` for a snapshot that fails if any file does.
+    pub fn collect_lenient(pid: PID) -> Result<LenientProcess> {
+        let process = try!(Process::new(pid));
+
+        return Ok(LenientProcess {
+            status:     process.status(),
+            memory:     process.memory(),
+            smaps:      process.memory_full_rollup(),
+            io:         process.io(),
+            cmdline:    process.cmdline_vec(),
+            process:    process
+        });
+    }
+
+    /// Read the process again, returning a new snapshot and leaving this one
+    /// as it was.
+    ///
+    /// Keeping both makes it simple to compare the two, e.g. with `diff` or
+    /// `CpuTimes::percent_since`. The new snapshot has nothing cached by
+    /// `status_cached`. If the process has exited, or its PID now belongs to
+    /// another process, this returns a `NotFound` error rather than reading the
+    /// new process (see `set_identity_check`).
+    pub fn reread(&self) -> Result<Process> {
+        let mut process = try!(Process::new(self.pid));
+        process.identity_check = self.identity_check;
+
+        if self.identity_check && !self.same_identity(&process) {
+            return Err(pid_reused(self.pid));
+        }
+
+        return Ok(process);
+    }
+
+    /// Create a Process by reading it's PID from a pidfile.
+    pub fn from_pidfile(path: &Path) -> Result<Process> {
+        Process::new(try!(read_pidfile(&path)))
+    }
+
+    /// Return `true` if both processes have the same PID.
+    ///
+    /// A PID can be reused once a process exits, so this can be `true` for two
+    /// unrelated processes. See `same_identity` for a stricter comparison.
+    pub fn same_pid(&self, other: &Process) -> bool {
+        self.pid == other.pid
+    }
+
+    /// Return `true` if both processes have the same PID and start time.
+    ///
+    /// This is the comparison used by `==`, and is only `true` if both values
+    /// were read from the same process.
+    pub fn same_identity(&self, other: &Process) -> bool {
+        self.same_pid(other) && self.starttime == other.starttime
+    }
+
+    /// Enable or disable the identity check made by methods that change or
+    /// signal the process.
+    ///
+    /// PIDs are reused once a process exits, so by the time a method like
+    /// `kill` or `set_nice` is called `self.pid` may belong to an unrelated
+    /// process. These methods first re-read `/proc/[pid]/stat` and check that
+    /// the start time still matches, which is enabled by default. Disabling it
+    /// saves a read for callers that accept the race. Even with the check there
+    /// is a small window between it and the system call in which the PID could
+    /// be reused.
+    pub fn set_identity_check(&mut self, enabled: bool) {
+        self.identity_check = enabled;
+    }
+
+    /// Check that `self.pid` still refers to the process this was read from.
+    ///
+    /// Returns a `NotFound` error if the process has exited, even if its PID
+    /// now belongs to another process.
+    fn check_identity(&self) -> Result<()> {
+        if !self.identity_check {
+            return Ok(());
+        }
+
+        if !self.same_identity(&try!(Process::new(self.pid))) {
+            return Err(pid_reused(self.pid));
+        }
+
+        return Ok(());
+    }
+
+    /// Return `true` if this is a process rather than one of its threads.
+    ///
+    /// `/proc` only lists processes, but `/proc/[tid]` still exists for the
+    /// ID of every other thread, so `Process::new` succeeds with a thread ID
+    /// and the result describes that thread. This compares `pid` with `tgid`.
+    /// If the status can't be read because the process has exited, this
+    /// returns `true`.
+    pub fn is_thread_group_leader(&self) -> bool {
+        self.tgid().map_or(true, |tgid| tgid == self.pid)
+    }
+
+    /// Return the thread group ID, which is the PID of the process this
+    /// belongs to.
+    ///
+    /// This is the `Tgid` field of `/proc/[pid]/status`, taken from
+    /// `status_cached` if it has been called. It is the same as `pid` unless
+    /// this was read from the ID of a thread other than the main thread.
+    pub fn tgid(&self) -> Result<PID> {
+        match self.status {
+            Some(ref status) => Ok(status.tgid),
+            None => Ok(try!(self.status()).tgid)
+        }
+    }
+
+    /// Read the process this thread belongs to.
+    ///
+    /// For a process read from its own PID this reads it again, and for one
+    /// read from a thread ID it reads the thread group leader instead.
+    pub fn thread_group_leader(&self) -> Result<Process> {
+        Process::new(try!(self.tgid()))
+    }
+
+    /// Return `true` if the process was alive at the time it was read.
+    pub fn is_alive(&self) -> bool {
+        match self.state {
+            State::Zombie => false,
+            _ => true
+        }
+    }
+
+    /// Return the name of the user that owns the process.
+    ///
+    /// This is the user `self.uid` belongs to, falling back to the UID as a
+    /// string if the password database has no entry for it. Names are cached
+    /// for each thread, so listing many processes only looks up each owner
+    /// once.
+    pub fn username(&self) -> Result<String> {
+        name_for_uid(self.uid)
+    }
+
+    /// Return the name of the group that owns the process.
+    ///
+    /// As with `username`, this falls back to `self.gid` as a string if there
+    /// is no entry for it in the group database, and is cached.
+    pub fn groupname(&self) -> Result<String> {
+        name_for_gid(self.gid)
+    }
+
+    /// Return `true` if the effective UID of the process is `uid`.
+    ///
+    /// The effective UID is taken from `status_cached` if it has been called.
+    /// Otherwise it is `self.uid`, the owner of `/proc/[pid]`, which is the
+    /// effective UID except for processes that are not dumpable (such as a
+    /// setuid program that changed its UID), whose files are owned by root.
+    pub fn is_running_as(&self, uid: UID) -> bool {
+        match self.status {
+            Some(ref status) => status.uid.effective == uid,
+            None => self.uid == uid
+        }
+    }
+
+    /// Return `true` if the effective UID of the process is root.
+    ///
+    /// This has the same caveats as `is_running_as`.
+    pub fn is_running_as_root(&self) -> bool {
+        self.is_running_as(0)
+    }
+
+    /// Return `true` if the process is called `name`, allowing for `comm`
+    /// being truncated.
+    ///
+    /// The kernel keeps only the first 15 bytes of a name, so a name of up to
+    /// 15 bytes is compared with `comm` directly. For a longer name, `comm`
+    /// must match its first 15 bytes and then the file name of `exe()` must
+    /// match it in full, so that two programs whose names share the first 15
+    /// bytes aren't confused. If the executable can't be read - it belongs to
+    /// another user, or the process is a zombie - the file name of `argv0()`
+    /// is used instead.
+    pub fn matches_name(&self, name: &str) -> Result<bool> {
+        let truncated = &name.as_bytes()[..min(name.len(), TASK_COMM_LEN - 1)];
+        if self.comm.as_bytes() != truncated {
+            return Ok(false);
+        }
+
+        if name.len() < TASK_COMM_LEN {
+            return Ok(true);
+        }
+
+        let path = match self.exe() {
+            Ok(Some(exe)) => Some(exe),
+            Ok(None) => None,
+            Err(ref e) if e.kind() == ErrorKind::PermissionDenied => None,
+            Err(e) => return Err(e)
+        };
+        let path = match path {
+            Some(path) => path,
+            None => match try!(self.argv0()) {
+                Some(argv0) => PathBuf::from(argv0),
+                None => return Ok(false)
+            }
+        };
+
+        return Ok(path.file_name().map_or(false, |file| file == name));
+    }
+
+    /// Read the current name of the process from `/proc/[pid]/comm`.
+    ///
+    /// A process can rename itself with `prctl(PR_SET_NAME)` or by writing to
+    /// `/proc/self/comm`, after which `comm` is stale. This is the name of the
+    /// main thread at the time it is called.
+    pub fn comm_live(&self) -> Result<String> {
+        let comm = try!(procfs(self.pid, "comm"));
+        Ok(comm.trim_right_matches('\n').to_string())
+    }
+
+    /// Read `/proc/[pid]/cmdline` as a vector.
+    ///
+    /// Returns `None` if `/proc/[pid]/cmdline` is empty, as it is for kernel
+    /// threads and zombies.
+    ///
+    /// Arguments are split on NUL bytes and spaces. A process that renames
+    /// itself with `setproctitle` usually leaves a single string with no NUL
+    /// bytes between words, such as `postgres: checkpointer`, and that is
+    /// returned as one element rather than split into words.
+    pub fn cmdline_vec(&self) -> Result<Option<Vec<String>>> {
+        Ok(self.args_from_cmdline(&try!(procfs_bytes(self.pid, "cmdline"))))
+    }
+
+    /// Split the contents of `/proc/[pid]/cmdline` into arguments, as for
+    /// `cmdline_vec`.
+    ///
+    /// A process that rewrites its title with `setproctitle` overwrites the
+    /// memory holding its arguments, and when the title doesn't end with a NUL
+    /// byte the kernel carries on reading into the environment that follows.
+    /// The contents are cut off at the end of the argument area given by the
+    /// `arg_start` and `arg_end` fields of `/proc/[pid]/stat`, so environment
+    /// variables don't appear as arguments. The kernel reports those fields as
+    /// zero for processes this process can't `ptrace`, and then the contents
+    /// are used as they are. Invalid UTF-8 is replaced with U+FFFD.
+    pub fn args_from_cmdline(&self, cmdline: &[u8]) -> Option<Vec<String>> {
+        let mut cmdline = cmdline;
+        if self.arg_end > self.arg_start {
+            let len = (self.arg_end - self.arg_start) as usize;
+            cmdline = &cmdline[..min(cmdline.len(), len)];
+        }
+
+        parse_cmdline(&String::from_utf8_lossy(cmdline))
+    }
+
+    /// Return the result of `cmdline_vec` as a String.
+    pub fn cmdline(&self) -> Result<Option<String>> {
+        Ok(try!(self.cmdline_vec()).and_then(|c| Some(c.join(" "))))
+    }
+
+    /// Read `/proc/[pid]/cmdline` as a vector of byte strings.
+    ///
+    /// Unlike `cmdline_vec`, arguments are only split on NUL bytes and are not
+    /// required to be UTF-8, so no data is lost. Returns `None` if
+    /// `/proc/[pid]/cmdline` is empty.
+    pub fn cmdline_bytes(&self) -> Result<Option<Vec<Vec<u8>>>> {
+        let mut cmdline = try!(procfs_bytes(self.pid, "cmdline"));
+
+        if cmdline.is_empty() {
+            return Ok(None);
+        }
+
+        if cmdline.last() == Some(&0) {
+            cmdline.pop();
+        }
+
+        return Ok(Some(cmdline.split(|b| *b == 0).map(|arg| arg.to_vec()).collect()));
+    }
+
+    /// Return the first argument in `/proc/[pid]/cmdline`, which is the name
+    /// the process was started with.
+    ///
+    /// This is what the process calls itself, and often differs from what is
+    /// actually running: login shells are started as `-bash`, a program run
+    /// through a symlink or `$PATH` has the name it was invoked as, and
+    /// `exec -a` or a launcher can set it to anything. `exe()` is the binary
+    /// that is really running, and `comm` is the first 15 bytes of its file
+    /// name unless the process has renamed itself.
+    ///
+    /// Unlike `cmdline_vec` the argument is not split on spaces, although a
+    /// process that overwrites its command line (like `setproctitle`) may
+    /// leave the whole title here. Invalid UTF-8 is replaced with U+FFFD.
+    /// Returns `None` if `/proc/[pid]/cmdline` is empty.
+    pub fn argv0(&self) -> Result<Option<String>> {
+        Ok(try!(self.cmdline_bytes()).and_then(|args| {
+            args.into_iter().next().map(|arg| String::from_utf8_lossy(&arg).into_owned())
+        }))
+    }
+
+    /// Read the target of the `/proc/[pid]/exe` link.
+    ///
+    /// If the executable has been deleted the kernel appends ` (deleted)` to
+    /// the link, which is removed here so the original path is returned. Use
+    /// `exe_raw` to tell if the file was deleted. Returns `None` for kernel
+    /// threads and zombies, which have no executable.
+    pub fn exe(&self) -> Result<Option<PathBuf>> {
+        Ok(try!(self.exe_raw()).map(strip_deleted_suffix))
+    }
+
+    /// Read the target of the `/proc/[pid]/exe` link as the kernel reports it.
+    pub fn exe_raw(&self) -> Result<Option<PathBuf>> {
+        optional(self.pid, fs::read_link(procfs_path(self.pid, "exe"))
+            .map_err(|e| Error::for_process(self.pid, e)))
+    }
+
+    /// Read the target of the `/proc/[pid]/exe` link as bytes.
+    ///
+    /// This is the unmodified link, as returned by `exe_raw`.
+    pub fn exe_bytes(&self) -> Result<Option<Vec<u8>>> {
+        Ok(try!(self.exe_raw()).map(|exe| exe.into_os_string().into_vec()))
+    }
+
+    /// Read the target of the `/proc/[pid]/cwd` link.
+    ///
+    /// The ` (deleted)` suffix is removed as for `exe` if the working directory
+    /// has been removed. Use `cwd_raw` to tell if it was. Returns `None` for
+    /// zombies, which have no working directory.
+    pub fn cwd(&self) -> Result<Option<PathBuf>> {
+        Ok(try!(self.cwd_raw()).map(strip_deleted_suffix))
+    }
+
+    /// Read the target of the `/proc/[pid]/cwd` link as the kernel reports it.
+    pub fn cwd_raw(&self) -> Result<Option<PathBuf>> {
+        optional(self.pid, fs::read_link(procfs_path(self.pid, "cwd"))
+            .map_err(|e| Error::for_process(self.pid, e)))
+    }
+
+    /// Reads `/proc/[pid]/io` into a struct.
+    ///
+    /// Returns `None` if the kernel was built without I/O accounting. Reading
+    /// the file for a process owned by another user requires the same
+    /// permissions as `ptrace(2)`, and returns a `PermissionDenied` error
+    /// otherwise.
+    pub fn io(&self) -> Result<Option<IoCounters>> {
+        match try!(optional(self.pid, procfs(self.pid, "io"))) {
+            Some(io) => Ok(Some(try!(FromStr::from_str(&io)))),
+            None => Ok(None)
+        }
+    }
+
+    /// Read the audit login UID from `/proc/[pid]/loginuid`.
+    ///
+    /// This is the UID of the user who logged in to start the session the
+    /// process belongs to, and doesn't change when the process switches user
+    /// with `su` or `sudo`. Returns `None` for processes started outside of a
+    /// login session, where it is unset.
+    pub fn loginuid(&self) -> Result<Option<UID>> {
+        read_audit_id(self.pid, "loginuid")
+    }
+
+    /// Read the audit session ID from `/proc/[pid]/sessionid`.
+    ///
+    /// Returns `None` for processes started outside of a login session.
+    pub fn audit_sessionid(&self) -> Result<Option<u32>> {
+        read_audit_id(self.pid, "sessionid")
+    }
+
+    /// Read `/proc/[pid]/environ` as a vector of `(name, value)` pairs.
+    ///
+    /// The names and values are left as bytes, as they are not guaranteed to
+    /// be UTF-8. Each entry is split on the first `=`.
+    pub fn environ_raw(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let environ = try!(procfs_bytes(self.pid, "environ"));
+
+        return Ok(environ.split(|b| *b == 0)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.iter().position(|b| *b == b'=') {
+                Some(i) => (entry[..i].to_vec(), entry[i+1..].to_vec()),
+                None => (entry.to_vec(), Vec::new())
+            })
+            .collect());
+    }
+
+    /// Return the time the process was started.
+    ///
+    /// This is calculated from `starttime` and the system boot time, which is
+    /// only known to the nearest second.
+    pub fn create_time(&self) -> Result<SystemTime> {
+        Ok(try!(boot_time()) + ticks_to_duration(self.starttime, self.cpu_ticks.ticks_per_second))
+    }
+
+    /// Return how long the process has been running.
+    ///
+    /// As the boot time is rounded to the nearest second, a process started
+    /// less than a second ago may have an age of zero.
+    pub fn age(&self) -> Result<Duration> {
+        let created = try!(self.create_time());
+        Ok(SystemTime::now().duration_since(created).unwrap_or(Duration::new(0, 0)))
+    }
+
+    /// Describe the process with derived values instead of raw fields.
+    ///
+    /// The derived `Debug` output shows start times in clock ticks, the RSS in
+    /// pages and memory addresses as plain numbers. This shows whether the
+    /// process is a thread group leader or a thread of another process, when
+    /// it was started (in UTC) and how long ago, and memory sizes in bytes,
+    /// in a multi-line format like `{:#?}`. Values that can't be read are
+    /// shown with the error instead.
+    pub fn debug_full(&self) -> String {
+        let group = match self.tgid() {
+            Ok(tgid) if tgid == self.pid => "thread group leader".to_string(),
+            Ok(tgid) => format!("thread of {}", tgid),
+            Err(e) => format!("thread group unknown: {}", e)
+        };
+        let started = match (self.create_time(), self.age()) {
+            (Ok(created), Ok(age)) => format!("{} ({}s ago)", format_utc(created), age.as_secs()),
+            (Err(e), _) | (_, Err(e)) => format!("unknown: {}", e)
+        };
+
+        return format!(concat!(
+            "Process {{\n",
+            "    pid: {} ({}),\n",
+            "    comm: {:?},\n",
+            "    state: {:?},\n",
+            "    ppid: {},\n",
+            "    uid: {}, gid: {},\n",
+            "    started: {},\n",
+            "    cpu: {:.2}s user, {:.2}s system,\n",
+            "    threads: {}, nice: {},\n",
+            "    vsize: {} bytes, rss: {} bytes\n",
+            "}}"),
+            self.pid, group, self.comm, self.state, self.ppid, self.uid, self.gid, started,
+            self.utime, self.stime, self.num_threads, self.nice,
+            self.vsize, max(self.rss, 0) as u64 * page_size());
+    }
+
+    /// Return `delayacct_blkio_ticks` as a `Duration`.
+    ///
+    /// This is the time the process has spent waiting for block I/O. It is
+    /// only counted when delay accounting is enabled in the kernel (with
+    /// `CONFIG_TASK_DELAY_ACCT` and the `delayacct` boot option or the
+    /// `kernel.task_delayacct` sysctl), and is zero otherwise.
+    pub fn blkio_delay(&self) -> Duration {
+        ticks_to_duration(self.delayacct_blkio_ticks, self.cpu_ticks.ticks_per_second)
+    }
+
+    /// Return the CPU time the process had been scheduled for in clock ticks.
+    pub fn cpu_times(&self) -> CpuTimes {
+        self.cpu_ticks
+    }
+
+    // The memory addresses from `/proc/[pid]/stat`. The kernel reports these
+    // as 0 unless the reader could attach to the process with `ptrace`.
+
+    /// Address above which the program text can run
+    pub fn startcode(&self) -> u64 {
+        self.startcode
+    }
+
+    /// Address below which the program text can run
+    pub fn endcode(&self) -> u64 {
+        self.endcode
+    }
+
+    /// Address of the start (bottom) of the stack
+    pub fn startstack(&self) -> u64 {
+        self.startstack
+    }
+
+    /// Current value of the stack pointer, as found in the kernel stack page
+    ///
+    /// This is only filled in for a thread that is stopped or exiting.
+    pub fn kstkesp(&self) -> u64 {
+        self.kstkesp
+    }
+
+    /// Current instruction pointer, as found in the kernel stack page
+    ///
+    /// This is only filled in for a thread that is stopped or exiting.
+    pub fn kstkeip(&self) -> u64 {
+        self.kstkeip
+    }
+
+    /// Address above which initialized and uninitialized (BSS) data are placed
+    pub fn start_data(&self) -> u64 {
+        self.start_data
+    }
+
+    /// Address below which initialized and uninitialized (BSS) data are placed
+    pub fn end_data(&self) -> u64 {
+        self.end_data
+    }
+
+    /// Address above which the heap can be expanded with `brk`
+    pub fn start_brk(&self) -> u64 {
+        self.start_brk
+    }
+
+    /// Address above which the command line arguments are placed
+    pub fn arg_start(&self) -> u64 {
+        self.arg_start
+    }
+
+    /// Address below which the command line arguments are placed
+    pub fn arg_end(&self) -> u64 {
+        self.arg_end
+    }
+
+    /// Address above which the environment is placed
+    pub fn env_start(&self) -> u64 {
+        self.env_start
+    }
+
+    /// Address below which the environment is placed
+    pub fn env_end(&self) -> u64 {
+        self.env_end
+    }
+
+    /// Sample the CPU times of the process at each end of an interval
+    ///
+    /// Returns the times at the start, the process as read at the end, and
+    /// how long the interval really took.
+    fn sample_cpu_times(&self, interval: Duration) -> Result<(CpuTimes, Process, Duration)> {
+        let before = try!(Process::new(self.pid));
+        let start = Instant::now();
+
+        thread::sleep(interval);
+
+        let after = try!(Process::new(self.pid));
+        if before != after {
+            return Err(Error::NoSuchProcess { pid: self.pid });
+        }
+
+        return Ok((before.cpu_times(), after, start.elapsed()));
+    }
+
+    /// Return the percentage of a CPU the process uses over an interval.
+    ///
+    /// This blocks for `interval`, reading the process again at the end.
+    /// Returns an error with the kind `NotFound` if the process exited.
+    ///
+    /// This is relative to a single CPU, as shown by `top` and `ps`, so a
+    /// process with several busy threads can use more than 100%. See
+    /// `cpu_percent_normalized` for a percentage of all CPUs.
+    pub fn cpu_percent(&self, interval: Duration) -> Result<f64> {
+        let (before, after, elapsed) = try!(self.sample_cpu_times(interval));
+        Ok(after.cpu_times().percent_since(&before, elapsed))
+    }
+
+    /// Read the common health signals of the process in one call.
+    ///
+    /// This **blocks** for 100 milliseconds to measure CPU usage, as with
+    /// `cpu_percent`, and the other values are from the end of that interval.
+    /// Returns an error with the kind `NotFound` if the process exited. The
+    /// file descriptor values need the same permissions as `ptrace(2)`, and
+    /// are `None` rather than an error when they can't be read, so a
+    /// monitoring tool can still show a row for every process.
+    pub fn health(&self) -> Result<ProcessHealth> {
+        let interval = Duration::from_millis(SAMPLE_INTERVAL_MS);
+        let (before, after, elapsed) = try!(self.sample_cpu_times(interval));
+        let cpu_percent = after.cpu_times().percent_since(&before, elapsed);
+
+        let num_fds = after.num_fds().ok();
+        let fd_limit = match after.get_rlimit(RLimit::NumFiles) {
+            Ok((soft, _)) if soft != RLIM_INFINITY => Some(soft),
+            _ => None
+        };
+        let fd_usage = match (num_fds, fd_limit) {
+            (Some(fds), Some(limit)) if limit > 0 => Some(fds as f64 / limit as f64),
+            _ => None
+        };
+
+        return Ok(ProcessHealth {
+            state: after.state,
+            cpu_percent: cpu_percent,
+            rss: max(after.rss, 0) as u64 * page_size(),
+            num_threads: after.num_threads,
+            num_fds: num_fds,
+            fd_limit: fd_limit,
+            fd_usage: fd_usage,
+            process: after
+        });
+    }
+
+    /// Return the percentage of a CPU the process uses in each mode over an
+    /// interval.
+    ///
+    /// This blocks for `interval` in the same way as `cpu_percent`, and the
+    /// user and system percentages add up to its result.
+    pub fn cpu_times_percent(&self, interval: Duration) -> Result<CpuTimesPercent> {
+        let (before, after, elapsed) = try!(self.sample_cpu_times(interval));
+        Ok(after.cpu_times().percent_breakdown_since(&before, elapsed))
+    }
+
+    /// Return the percentage of all CPUs the process uses over an interval.
+    ///
+    /// This is `cpu_percent` divided by the number of online logical CPUs,
+    /// so it is between 0 and 100 however many threads the process has. This
+    /// matches `top` with Irix mode turned off.
+    pub fn cpu_percent_normalized(&self, interval: Duration) -> Result<f64> {
+        Ok(try!(self.cpu_percent(interval)) / cpu_count() as f64)
+    }
+
+    /// Estimate the energy used by the process over an interval, in joules.
+    ///
+    /// This blocks for `interval`, sampling the CPU time of the process and
+    /// of the whole system, and the package energy counters from Intel RAPL
+    /// (`/sys/class/powercap/intel-rapl:*/energy_uj`) at each end.
+    ///
+    /// The model is deliberately simple: the energy used by all packages is
+    /// shared between processes in proportion to the CPU time each was
+    /// scheduled for, out of the time all CPUs spent busy. It ignores that
+    /// different work draws different power, and that package energy also
+    /// covers idle power and the uncore. Reading `energy_uj` usually requires
+    /// root, and systems without RAPL return a `NotFound` error.
+    pub fn cpu_energy_estimate(&self, interval: Duration) -> Result<f64> {
+        let energy_before = try!(rapl_energy());
+        let system_before = try!(cpu_times());
+        let process_before = try!(Process::new(self.pid));
+
+        thread::sleep(interval);
+
+        let energy_after = try!(rapl_energy());
+        let system_after = try!(cpu_times());
+        let process_after = try!(Process::new(self.pid));
+
+        if process_before != process_after {
+            return Err(Error::NoSuchProcess { pid: self.pid });
+        }
+
+        let process_time = (process_after.utime + process_after.stime)
+            - (process_before.utime + process_before.stime);
+        let busy_time = system_after.busy() - system_before.busy();
+
+        return Ok(cpu_energy_share(&energy_before, &energy_after, process_time, busy_time));
+    }
+
+    /// Reads detailed memory usage, including PSS and USS.
+    ///
+    /// This reads `/proc/[pid]/smaps_rollup` on Linux 4.14 and later, which
+    /// the kernel sums and is cheap enough to read for every process. Older
+    /// kernels fall back to summing `/proc/[pid]/smaps`, which has an entry
+    /// for every mapping. Reading either for a process owned by another user
+    /// requires the same permissions as `ptrace(2)`.
+    pub fn memory_full_rollup(&self) -> Result<MemoryFull> {
+        let smaps = match try!(optional(self.pid, procfs(self.pid, "smaps_rollup"))) {
+            Some(rollup) => rollup,
+            None => try!(procfs(self.pid, "smaps"))
+        };
+
+        MemoryFull::from_smaps(&smaps)
+    }
+
+    /// Poll `/proc/[pid]/stat` until the process is in the `target` state.
+    ///
+    /// This blocks until the state is reached, returning `Ok(true)`, or until
+    /// `timeout` has elapsed, returning `Ok(false)`. A process that stops on a
+    /// signal is `State::Traced`. Returns an error with the kind `NotFound` if
+    /// the process exits first, which includes becoming a zombie when the
+    /// target is any other state.
+    pub fn wait_for_state(&self, target: State, timeout: Duration) -> Result<bool> {
+        let start = Instant::now();
+
+        loop {
+            let process = try!(Process::new(self.pid));
+            if !self.same_identity(&process) || (process.state == State::Zombie && target != State::Zombie) {
+                return Err(Error::NoSuchProcess { pid: self.pid });
+            }
+
+            if process.state == target {
+                return Ok(true);
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Ok(false);
+            }
+            thread::sleep(min(timeout - elapsed, Duration::from_millis(10)));
+        }
+    }
+
+    /// Return the name of the controlling terminal, as shown by `ps`.
+    ///
+    /// This is the path of the device relative to `/dev`, such as `pts/3` or
+    /// `tty1`, decoded from `tty_nr`. Returns `None` if the process has no
+    /// controlling terminal. Devices other than virtual consoles, serial
+    /// ports and pseudoterminals are looked up in `/dev`, and are named by
+    /// their device numbers as `major:minor` if they aren't found there.
+    pub fn terminal_name(&self) -> Result<Option<String>> {
+        if self.tty_nr == 0 {
+            return Ok(None);
+        }
+
+        // The minor number is split around the major number, see `new_encode_dev`
+        let tty_nr = self.tty_nr as u32;
+        let major = (tty_nr >> 8) & 0xfff;
+        let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+
+        let name = match (major, minor) {
+            (4, 0..=63) => format!("tty{}", minor),
+            (4, _) => format!("ttyS{}", minor - 64),
+            (5, 0) => "tty".to_string(),
+            (5, 1) => "console".to_string(),
+            (136..=143, _) => format!("pts/{}", (major - 136) * 256 + minor),
+            _ => match try!(find_device(Path::new("/dev"), major, minor)) {
+                Some(name) => name,
+                None => format!("{}:{}", major, minor)
+            }
+        };
+
+        return Ok(Some(name));
+    }
+
+    /// Return the kernel function the process is blocked in, if any.
+    ///
+    /// This is the symbol in `/proc/[pid]/wchan` when the kernel provides one.
+    /// Many kernels report `0` there to avoid leaking addresses, in which case
+    /// this falls back to the top frame of `/proc/[pid]/stack`. Reading the
+    /// stack requires `CAP_SYS_ADMIN` (or `ptrace` access), and without it
+    /// the fallback is skipped. Returns `Ok(None)` when the process is running
+    /// or neither source is available.
+    pub fn blocked_in(&self) -> Result<Option<String>> {
+        if let Some(wchan) = try!(optional(self.pid, procfs(self.pid, "wchan"))) {
+            let wchan = wchan.trim();
+            if !wchan.is_empty() && wchan != "0" {
+                return Ok(Some(wchan.to_string()));
+            }
+        }
+
+        return match optional(self.pid, procfs(self.pid, "stack")) {
+            Ok(stack) => Ok(stack.and_then(|s| stack_top_function(&s))),
+            Err(ref e) if e.kind() == ErrorKind::PermissionDenied => Ok(None),
+            Err(e) => Err(e)
+        };
+    }
+
+    /// Reads `/proc/[pid]/statm` into a struct.
+    ///
+    /// A zombie has released its memory, so if the process was a zombie when
+    /// it was loaded every field is zero and the file is not read.
+    pub fn memory(&self) -> Result<Memory> {
+        match self.state {
+            State::Zombie => Ok(Memory::default()),
+            _ => Memory::new(self.pid)
+        }
+    }
+
+    /// Reads `/proc/[pid]/status` into a struct.
+    ///
+    /// The file is read again on every call. Use `status_cached` to read
+    /// several fields without re-reading the file each time.
+    pub fn status(&self) -> Result<Status> {
+        Status::new(self.pid)
+    }
+
+    /// Reads `/proc/[pid]/status` the first time it is called, and returns
+    /// the same `Status` on every later call.
+    ///
+    /// The cached values are never refreshed, so they become stale as the
+    /// process runs - fields like `vm_rss` and the context switch counts only
+    /// describe the process as it was on the first call.
+    pub fn status_cached(&mut self) -> Result<&Status> {
+        if self.status.is_none() {
+            self.status = Some(try!(self.status()));
+        }
+
+        return Ok(self.status.as_ref().unwrap());
+    }
+
+    /// Reads the capability sets of the process from `/proc/[pid]/status`.
+    ///
+    /// A set missing from the file, such as the ambient set before Linux 4.3,
+    /// is empty.
+    pub fn capabilities(&self) -> Result<Capabilities> {
+        let status = try!(self.status());
+        let set = |mask: Option<u64>| Capability::from_mask(mask.unwrap_or(0));
+
+        return Ok(Capabilities {
+            inheritable: set(status.cap_inh),
+            permitted: set(status.cap_prm),
+            effective: set(status.cap_eff),
+            bounding: set(status.cap_bnd),
+            ambient: set(status.cap_amb)
+        });
+    }
+
+    /// Reads the signal masks of the process from `/proc/[pid]/status`.
+    ///
+    /// This shows why a process doesn't react to a signal: it may be blocked
+    /// and left pending, ignored, or caught by a handler that does nothing.
+    pub fn signal_masks(&self) -> Result<SignalMasks> {
+        let status = try!(self.status());
+        let set = |mask: Option<u64>| Signal::from_mask(mask.unwrap_or(0));
+
+        return Ok(SignalMasks {
+            pending: set(status.sig_pnd),
+            shared_pending: set(status.shd_pnd),
+            blocked: set(status.sig_blk),
+            ignored: set(status.sig_ign),
+            caught: set(status.sig_cgt)
+        });
+    }
+
+    /// Reads the seccomp mode of the process from `/proc/[pid]/status`.
+    ///
+    /// Kernels built without `CONFIG_SECCOMP` don't show the mode, and every
+    /// process is reported as `SeccompMode::Disabled`. The number of filters
+    /// is available from `Status::seccomp_filters` on Linux 5.9 and later.
+    pub fn seccomp_mode(&self) -> Result<SeccompMode> {
+        match try!(self.status()).seccomp {
+            None | Some(0) => Ok(SeccompMode::Disabled),
+            Some(1) => Ok(SeccompMode::Strict),
+            Some(2) => Ok(SeccompMode::Filter),
+            Some(mode) => Err(Error::new(ErrorKind::Other,
+                format!("Unknown seccomp mode {} in /proc/[pid]/status", mode)))
+        }
+    }
+
+    /// Reads whether the `no_new_privs` bit is set from `/proc/[pid]/status`.
+    ///
+    /// Once set, `execve(2)` never grants privileges, such as those of setuid
+    /// programs or file capabilities. The bit is only shown on Linux 4.10 and
+    /// later, and older kernels return an error with the kind `Other`.
+    pub fn no_new_privs(&self) -> Result<bool> {
+        try!(self.status()).no_new_privs.ok_or(Error::new(ErrorKind::Other,
+            "Missing NoNewPrivs in /proc/[pid]/status"))
+    }
+
+    /// Reads `/proc/[pid]/fdinfo/[fd]` into a struct.
+    pub fn fdinfo(&self, fd: i32) -> Result<FdInfo> {
+        FdInfo::new(self.pid, fd)
+    }
+
+    /// Read the targets of each link in `/proc/[pid]/fd`.
+    ///
+    /// File descriptors closed while the directory is being read are skipped.
+    fn fd_links(&self) -> Result<Vec<(i32, PathBuf)>> {
+        let mut links = Vec::new();
+
+        let dir = procfs_path(self.pid, "fd");
+        for entry in try!(read_dir(dir).map_err(|e| Error::for_process(self.pid, e))) {
+            let entry = try!(entry.map_err(|e| Error::for_process(self.pid, e)));
+            let fd = match FromStr::from_str(&entry.file_name().to_string_lossy()) {
+                Ok(fd) => fd,
+                Err(_) => continue
+            };
+
+            match fs::read_link(entry.path()) {
+                Ok(path) => links.push((fd, path)),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(Error::for_process(self.pid, e))
+            }
+        }
+
+        return Ok(links);
+    }
+
+    /// Return the inodes of the sockets the process has open.
+    ///
+    /// These match the `inode` field of the sockets in `network::connections`.
+    /// A socket open on several file descriptors is only listed once.
+    pub fn socket_inodes(&self) -> Result<Vec<u64>> {
+        let mut inodes = Vec::new();
+
+        for (_, target) in try!(self.fd_links()) {
+            let target = target.to_string_lossy();
+            if target.starts_with("socket:[") && target.ends_with(']') {
+                if let Ok(inode) = target[8..target.len() - 1].parse() {
+                    inodes.push(inode);
+                }
+            }
+        }
+
+        inodes.sort();
+        inodes.dedup();
+        return Ok(inodes);
+    }
+
+    /// Count the open file descriptors of the process by type.
+    ///
+    /// Only the links in `/proc/[pid]/fd` are read, so this is much cheaper
+    /// than `open_files`.
+    pub fn fd_summary(&self) -> Result<FdSummary> {
+        let mut summary = FdSummary::default();
+        for (_, target) in try!(self.fd_links()) {
+            summary.add(&target);
+        }
+        return Ok(summary);
+    }
+
+    /// Count the open file descriptors of the process.
+    ///
+    /// Only the entries of `/proc/[pid]/fd` are listed, without reading their
+    /// links, so this is cheaper still than `fd_summary`.
+    pub fn num_fds(&self) -> Result<usize> {
+        let mut count = 0;
+        let dir = procfs_path(self.pid, "fd");
+        for entry in try!(read_dir(dir).map_err(|e| Error::for_process(self.pid, e))) {
+            try!(entry.map_err(|e| Error::for_process(self.pid, e)));
+            count += 1;
+        }
+        return Ok(count);
+    }
+
+    /// Read every open file descriptor of the process, including sockets,
+    /// pipes and other non-filesystem targets.
+    pub fn open_files(&self) -> Result<Vec<OpenFile>> {
+        let mut files = Vec::new();
+
+        for (fd, path) in try!(self.fd_links()) {
+            match OpenFile::new(self.pid, fd, path) {
+                Ok(file) => files.push(file),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e)
+            }
+        }
+
+        return Ok(files);
+    }
+
+    /// Read the open file descriptors that refer to regular files.
+    ///
+    /// Sockets, pipes, anonymous inodes, devices and files that have been
+    /// deleted since they were opened are skipped.
+    pub fn open_regular_files(&self) -> Result<Vec<OpenFile>> {
+        let mut files = Vec::new();
+
+        for (fd, path) in try!(self.fd_links()) {
+            if !path.is_absolute() {
+                continue;
+            }
+
+            if path.to_string_lossy().ends_with(DELETED_SUFFIX) {
+                continue;
+            }
+
+            // The link is followed by `metadata`, so this describes the file
+            // the descriptor refers to rather than the link itself
+            match fs::metadata(procfs_path(self.pid, &format!("fd/{}", fd))) {
+                Ok(ref meta) if meta.is_file() => (),
+                Ok(_) => continue,
+                Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(Error::for_process(self.pid, e))
+            }
+
+            match OpenFile::new(self.pid, fd, path) {
+                Ok(file) => files.push(file),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e)
+            }
+        }
+
+        return Ok(files);
+    }
+
+    /// Estimate the disk space used by the regular files the process has open.
+    ///
+    /// Each file is measured with `stat` through `/proc/[pid]/fd/[fd]`, and
+    /// the space allocated to it on disk is counted once even if it is open
+    /// on several descriptors. Files deleted while open are included, as they
+    /// still use space (see `deleted_open_files`).
+    ///
+    /// This is only a heuristic: it is the size of the whole file rather than
+    /// what the process wrote to it, files shared with other processes are
+    /// counted for each of them, and files that are opened and closed between
+    /// samples are never seen. Files on filesystems that don't report
+    /// allocated blocks count as zero.
+    pub fn disk_usage_of_open_files(&self) -> Result<u64> {
+        let mut seen = HashSet::new();
+        let mut total = 0u64;
+
+        for (fd, _) in try!(self.fd_links()) {
+            let meta = match fs::metadata(procfs_path(self.pid, &format!("fd/{}", fd))) {
+                Ok(meta) => meta,
+                Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(Error::for_process(self.pid, e))
+            };
+
+            if meta.is_file() && seen.insert((meta.dev(), meta.ino())) {
+                total = total.saturating_add(meta.blocks().saturating_mul(512));
+            }
+        }
+
+        return Ok(total);
+    }
+
+    /// Find regular files the process has open that have since been deleted.
+    ///
+    /// Returns the descriptor, the path the file had before it was deleted,
+    /// and its current size in bytes. Such files keep using disk space until
+    /// every descriptor for them is closed, which is the usual reason for a
+    /// full filesystem that `du` can't account for. A file is only included
+    /// if it has no links left, so a file that happens to be named
+    /// `* (deleted)` isn't mistaken for one. Files created with
+    /// `memfd_create(2)` are skipped, as they use memory rather than disk.
+    pub fn deleted_open_files(&self) -> Result<Vec<(i32, PathBuf, u64)>> {
+        let mut files = Vec::new();
+
+        for (fd, path) in try!(self.fd_links()) {
+            let target = path.to_string_lossy().into_owned();
+            if !target.starts_with('/') || !target.ends_with(DELETED_SUFFIX)
+                || target.starts_with("/memfd:") {
+                continue;
+            }
+
+            match fs::metadata(procfs_path(self.pid, &format!("fd/{}", fd))) {
+                Ok(ref meta) if meta.is_file() && meta.nlink() == 0 => {
+                    files.push((fd, strip_deleted_suffix(path), meta.len()));
+                },
+                Ok(_) => (),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(Error::for_process(self.pid, e))
+            }
+        }
+
+        return Ok(files);
+    }
+
+    /// Return `true` if `ancestor` is the parent of the process, or of one of
+    /// its ancestors.
+    ///
+    /// The walk up through each parent stops at PID 1 or PID 0, and is bounded
+    /// by the number of processes on the system in case PID reuse has created
+    /// a loop. A process is not a descendant of itself.
+    pub fn is_descendant_of(&self, ancestor: PID) -> Result<bool> {
+        let limit = try!(pid_count());
+        let mut current = self.ppid;
+
+        for _ in 0..limit {
+            if current == ancestor {
+                return Ok(true);
+            }
+
+            if current <= 1 {
+                return Ok(false);
+            }
+
+            current = try!(Process::new(current)).ppid;
+        }
+
+        return Ok(false);
+    }
+
+    /// Return the soft and hard limit of a resource.
+    ///
+    /// A value of `RLIM_INFINITY` means the resource is not limited.
+    pub fn get_rlimit(&self, resource: RLimit) -> Result<(u64, u64)> {
+        let mut limit = rlimit64 { rlim_cur: 0, rlim_max: 0 };
+
+        return match unsafe { prlimit64(self.pid, resource as c_int, ptr::null(), &mut limit) } {
+            0 => Ok((limit.rlim_cur, limit.rlim_max)),
+            _ => Err(Error::for_process(self.pid, Error::last_os_error()))
+        };
+    }
+
+    /// Set the soft and hard limit of a resource.
+    ///
+    /// Raising a hard limit, or changing the limits of a process owned by
+    /// another user, requires `CAP_SYS_RESOURCE` and otherwise returns a
+    /// `PermissionDenied` error. Returns a `NotFound` error if the process has
+    /// exited or its PID has been reused.
+    pub fn set_rlimit(&self, resource: RLimit, soft: u64, hard: u64) -> Result<()> {
+        try!(self.check_identity());
+        let limit = rlimit64 { rlim_cur: soft, rlim_max: hard };
+
+        return match unsafe { prlimit64(self.pid, resource as c_int, &limit, ptr::null_mut()) } {
+            0 => Ok(()),
+            _ => match Error::last_os_error() {
+                ref e if e.raw_os_error() == Some(EPERM) => {
+                    Err(Error::new(ErrorKind::PermissionDenied, format!(
+                        "Not permitted to set {:?} limit of process {}", resource, self.pid)))
+                },
+                e => Err(Error::for_process(self.pid, e))
+            }
+        };
+    }
+
+    /// Return the CPUs the process is allowed to run on.
+    ///
+    /// This is the affinity of the main thread, which other threads inherit
+    /// when they are created but may have changed since.
+    pub fn get_cpu_affinity(&self) -> Result<Vec<usize>> {
+        self.cpu_affinity().map(|cpus| cpus.iter().collect())
+    }
+
+    /// Restrict the process to running on the given CPUs.
+    ///
+    /// Only the main thread is changed, as `sched_setaffinity(2)` applies to
+    /// a single thread. Use `Thread::set_cpu_affinity` on each of `threads`
+    /// to move threads that already exist. An empty list of CPUs, or one with
+    /// no online CPUs in it, returns an `InvalidInput` error. Changing the
+    /// affinity of a process owned by another user requires `CAP_SYS_NICE`,
+    /// and returns a `PermissionDenied` error otherwise. As with `set_nice`,
+    /// a process whose PID has been reused returns a `NotFound` error.
+    pub fn set_cpu_affinity(&self, cpus: &[usize]) -> Result<()> {
+        self.set_cpu_affinity_mask(&CpuSet::from(cpus))
+    }
+
+    /// Return the CPUs the process is allowed to run on as a `CpuSet`.
+    ///
+    /// See `get_cpu_affinity`.
+    pub fn cpu_affinity(&self) -> Result<CpuSet> {
+        get_affinity(self.pid)
+    }
+
+    /// Restrict the process to running on the CPUs in a `CpuSet`.
+    ///
+    /// See `set_cpu_affinity`, which this behaves the same as.
+    pub fn set_cpu_affinity_mask(&self, cpus: &CpuSet) -> Result<()> {
+        try!(self.check_identity());
+        set_affinity(self.pid, cpus)
+    }
+
+    /// Set the nice value of the process.
+    ///
+    /// Returns an `InvalidInput` error if `nice` is outside of `-20..19`.
+    /// Any process may raise its own nice value, but lowering it or changing
+    /// the nice value of a process owned by another user requires
+    /// `CAP_SYS_NICE` and otherwise returns a `PermissionDenied` error. If the
+    /// process has exited, or its PID has been reused, this returns a
+    /// `NotFound` error (see `set_identity_check`).
+    pub fn set_nice(&self, nice: i64) -> Result<()> {
+        if nice < NICE_MIN || nice > NICE_MAX {
+            return Err(Error::new(ErrorKind::InvalidInput, format!(
+                "Nice value {} is outside of {}..{}", nice, NICE_MIN, NICE_MAX)));
+        }
+
+        try!(self.check_identity());
+
+        return match unsafe { setpriority(PRIO_PROCESS, self.pid as UID, nice as c_int) } {
+            0 => Ok(()),
+            _ => match Error::last_os_error() {
+                ref e if e.raw_os_error() == Some(EPERM) || e.raw_os_error() == Some(EACCES) => {
+                    Err(Error::new(ErrorKind::PermissionDenied, format!(
+                        "Setting the nice value of process {} to {} requires CAP_SYS_NICE",
+                        self.pid, nice)))
+                },
+                e => Err(Error::for_process(self.pid, e))
+            }
+        };
+    }
+
+    /// Add `delta` to the current nice value of the process.
+    ///
+    /// The current value is read from `/proc/[pid]/stat` rather than taken
+    /// from `self.nice`, and the result is clamped to `-20..19`. Permissions
+    /// are checked as for `set_nice`.
+    pub fn set_nice_relative(&self, delta: i64) -> Result<()> {
+        let current = try!(Process::new(self.pid)).nice;
+        self.set_nice(current.saturating_add(delta).max(NICE_MIN).min(NICE_MAX))
+    }
+
+    /// Move the process into the background, so that it only uses CPU and
+    /// disk time that other processes don't need.
+    ///
+    /// This sets the I/O scheduling class to idle and then the nice value to
+    /// 19. Both are per-thread settings, so as with `set_nice` only the main
+    /// thread and threads it creates afterwards are changed. Deprioritizing a
+    /// process owned by another user requires `CAP_SYS_NICE` and otherwise
+    /// returns a `PermissionDenied` error. On architectures where the number
+    /// of `ioprio_set` isn't known this returns an `Other` error without
+    /// changing anything.
+    ///
+    /// The two are not changed atomically: if the I/O priority can't be set
+    /// nothing has been changed, but if the nice value can't be set after it
+    /// the I/O priority is left idle. Both need the same permission, so this
+    /// only happens if the process exits in between.
+    ///
+    /// Lowering the cgroup weight is best effort. If the process is in a
+    /// cgroup v2 with the `cpu` controller enabled, the `cpu.weight` of the
+    /// cgroup is lowered to the minimum - which affects every process in that
+    /// cgroup, so this is best used on a process that has a cgroup of its
+    /// own. The cgroup is skipped without an error if it has no `cpu.weight`,
+    /// or if this process isn't permitted to write to it, which is usual
+    /// unless the cgroup has been delegated to the calling user.
+    pub fn deprioritize(&self) -> Result<()> {
+        let cgroup = try!(cgroup_dir(self.pid, None));
+        self.deprioritize_in(cgroup.as_ref().map(|dir| dir.as_path()))
+    }
+
+    /// Deprioritize the process as `deprioritize` does, lowering the
+    /// `cpu.weight` in the cgroup directory `cgroup` rather than the one the
+    /// process belongs to, or leaving cgroups alone if it is `None`.
+    pub fn deprioritize_in(&self, cgroup: Option<&Path>) -> Result<()> {
+        let ioprio_set = try!(SYS_IOPRIO_SET.ok_or_else(|| Error::new(ErrorKind::Other,
+            "Setting the I/O priority is unsupported on this architecture")));
+
+        try!(self.check_identity());
+
+        if unsafe { syscall(ioprio_set, IOPRIO_WHO_PROCESS, self.pid, IOPRIO_IDLE) } != 0 {
+            return Err(match Error::last_os_error() {
+                ref e if e.raw_os_error() == Some(EPERM) => {
+                    Error::new(ErrorKind::PermissionDenied, format!(
+                        "Setting the I/O priority of process {} requires CAP_SYS_NICE",
+                        self.pid))
+                },
+                e => Error::for_process(self.pid, e)
+            });
+        }
+
+        try!(self.set_nice(NICE_MAX));
+
+        if let Some(dir) = cgroup {
+            let weight = OpenOptions::new().write(true).open(dir.join("cpu.weight"))
+                .and_then(|mut file| file.write_all(CGROUP_CPU_WEIGHT_MIN.as_bytes()));
+
+            match weight {
+                Ok(()) => (),
+                Err(ref e) if e.kind() == ErrorKind::NotFound
+                    || e.kind() == ErrorKind::PermissionDenied => (),
+                Err(e) => return Err(Error::from(e))
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Read an OOM adjustment from `/proc/[pid]/oom_score_adj` or `oom_adj`
+    fn read_oom(&self, name: &str) -> Result<i32> {
+        try!(procfs(self.pid, name)).trim().parse().map_err(|_| {
+            Error::new(ErrorKind::Other, format!("Could not parse /proc/[pid]/{}", name))
+        })
+    }
+
+    /// Write an OOM adjustment, checking it is in range first
+    fn write_oom(&self, name: &str, value: i32, min: i32, max: i32) -> Result<()> {
+        if value < min || value > max {
+            return Err(Error::new(ErrorKind::InvalidInput, format!(
+                "{} value {} is outside of {}..{}", name, value, min, max)));
+        }
+
+        try!(self.check_identity());
+        let result = OpenOptions::new().write(true).open(procfs_path(self.pid, name))
+            .and_then(|mut file| file.write_all(value.to_string().as_bytes()));
+
+        return match result {
+            Err(ref e) if e.kind() == ErrorKind::PermissionDenied => {
+                Err(Error::new(ErrorKind::PermissionDenied, format!(
+                    "Setting {} of process {} to {} requires CAP_SYS_RESOURCE",
+                    name, self.pid, value)))
+            },
+            result => result.map_err(|e| Error::for_process(self.pid, e))
+        };
+    }
+
+    /// Read `/proc/[pid]/oom_score_adj`, which is added to the score used to
+    /// choose a process for the OOM killer.
+    ///
+    /// The range is `-1000..1000`, where -1000 stops the process from being
+    /// killed and 1000 makes it the first choice.
+    pub fn oom_score_adj(&self) -> Result<i32> {
+        self.read_oom("oom_score_adj")
+    }
+
+    /// Write `/proc/[pid]/oom_score_adj`.
+    ///
+    /// Returns an `InvalidInput` error if `adj` is outside of `-1000..1000`.
+    /// Lowering the value below the lowest it has been set to requires
+    /// `CAP_SYS_RESOURCE`, and otherwise returns a `PermissionDenied` error.
+    pub fn set_oom_score_adj(&self, adj: i32) -> Result<()> {
+        self.write_oom("oom_score_adj", adj, OOM_SCORE_ADJ_MIN, OOM_SCORE_ADJ_MAX)
+    }
+
+    /// Read the legacy `/proc/[pid]/oom_adj`, in the range `-17..15`.
+    ///
+    /// This is deprecated in favour of `oom_score_adj`, but older tools still
+    /// use it. The kernel keeps both files in sync by scaling one to the other,
+    /// so writing either changes both - although as the ranges differ a value
+    /// may not survive the round trip exactly.
+    pub fn oom_adj(&self) -> Result<i32> {
+        self.read_oom("oom_adj")
+    }
+
+    /// Write the legacy `/proc/[pid]/oom_adj`.
+    ///
+    /// Returns an `InvalidInput` error if `adj` is outside of `-17..15`, and
+    /// checks permissions as for `set_oom_score_adj`. Prefer that method for
+    /// new code, as the kernel logs a deprecation warning when this is used.
+    pub fn set_oom_adj(&self, adj: i32) -> Result<()> {
+        self.write_oom("oom_adj", adj, OOM_ADJ_MIN, OOM_ADJ_MAX)
+    }
+
+    /// Read traffic counters for each network interface from
+    /// `/proc/[pid]/net/dev`.
+    ///
+    /// These are the interfaces of the network namespace of the process, so
+    /// for a process in a container with its own namespace they are the
+    /// container's interfaces rather than those of the host.
+    pub fn net_io_counters(&self) -> Result<HashMap<String, NetIoCounters>> {
+        procfs_with(self.pid, "net/dev", parse_net_dev)
+    }
+
+    /// Read the internet sockets of the given kind that the process has open.
+    ///
+    /// Sockets are read from `/proc/[pid]/net`, so they are those of the
+    /// network namespace of the process, and `pid` is set to this process.
+    /// Reading another user's file descriptors requires `CAP_SYS_PTRACE`, and
+    /// otherwise returns a `PermissionDenied` error.
+    pub fn connections(&self, kind: ConnKind) -> Result<Vec<Connection>> {
+        self.connections_matching(kind, |_| true)
+    }
+
+    /// Read the internet sockets of the process that are in one of `states`.
+    ///
+    /// This is the same as filtering `connections(ConnKind::Inet)`, but each
+    /// table is filtered as it is read. Counting the `Established` sockets of
+    /// a server over time is a simple way to spot a connection leak.
+    pub fn connections_by_state(&self, states: &[TcpState]) -> Result<Vec<Connection>> {
+        self.connections_matching(ConnKind::Inet, |c| states.contains(&c.state))
+    }
+
+    /// Read the sockets of the process of the given kind that pass `filter`
+    fn connections_matching<F: Fn(&Connection) -> bool>(&self, kind: ConnKind, filter: F)
+        -> Result<Vec<Connection>> {
+        let inodes: HashSet<u64> = try!(self.socket_inodes()).into_iter().collect();
+        let mut connections = Vec::new();
+
+        for &(name, protocol) in kind.tables() {
+            let contents = match optional(self.pid, procfs(self.pid, &format!("net/{}", name))) {
+                Ok(Some(contents)) => contents,
+                Ok(None) => continue,
+                Err(e) => return Err(e)
+            };
+
+            for mut connection in try!(parse_net_sockets(&contents, protocol)) {
+                if inodes.contains(&connection.inode) && filter(&connection) {
+                    connection.pid = Some(self.pid);
+                    connections.push(connection);
+                }
+            }
+        }
+
+        return Ok(connections);
+    }
+
+    /// Read `/proc/[pid]/stat` again as a vector of unparsed fields.
+    ///
+    /// The fields are split in the same way as when constructing a `Process`:
+    /// the first is the PID and the second is the process name without its
+    /// surrounding `()`, even if it contains spaces. Indexes match the field
+    /// numbers in `proc(5)` minus one, which is useful for fields this struct
+    /// doesn't model, or for checking what the parsed fields were read from.
+    pub fn stat_raw(&self) -> Result<Vec<String>> {
+        let stat = try!(procfs_bytes(self.pid, "stat"));
+        let stat = String::from_utf8_lossy(&stat);
+        let fields = try!(Process::split_stat(&stat));
+        return Ok(fields.into_iter().map(|f| f.to_string()).collect());
+    }
+
+    /// Read `/proc/[pid]/maps` as a vector of memory mappings.
+    ///
+    /// Reading the mappings of a process owned by another user requires
+    /// `CAP_SYS_PTRACE`, and otherwise returns a `PermissionDenied` error.
+    pub fn memory_maps(&self) -> Result<Vec<MemoryMap>> {
+        self.memory_maps_matching(|_| true)
+    }
+
+    /// Read the memory mappings of the process whose permissions pass `filter`.
+    pub fn memory_maps_matching<F: Fn(Perms) -> bool>(&self, filter: F) -> Result<Vec<MemoryMap>> {
+        let mut maps = Vec::new();
+        for line in try!(procfs(self.pid, "maps")).lines() {
+            let map: MemoryMap = try!(FromStr::from_str(line));
+            if filter(map.perms) {
+                maps.push(map);
+            }
+        }
+        return Ok(maps);
+    }
+
+    /// Read `/proc/[pid]/smaps` as the memory usage and flags of each mapping.
+    ///
+    /// The kernel walks the page tables of every mapping to produce this, so
+    /// it is much slower than `memory_maps`, and `memory_full_rollup` should
+    /// be used if only the totals are needed. Reading it for a process owned
+    /// by another user requires `CAP_SYS_PTRACE`.
+    pub fn smaps(&self) -> Result<Vec<SmapsEntry>> {
+        procfs_with(self.pid, "smaps", SmapsEntry::from_smaps)
+    }
+
+    /// Read `/proc/[pid]/numa_maps` as the NUMA placement of each mapping.
+    ///
+    /// Like `smaps`, this walks the page tables of every mapping, and reading
+    /// it for a process owned by another user requires `CAP_SYS_PTRACE`.
+    /// Kernels built without NUMA support don't have the file, and return an
+    /// error with the kind `Other`.
+    pub fn numa_maps(&self) -> Result<Vec<NumaMap>> {
+        let numa_maps = match try!(optional(self.pid, procfs(self.pid, "numa_maps"))) {
+            Some(numa_maps) => numa_maps,
+            None => return Err(Error::new(ErrorKind::Other,
+                "NUMA is not supported by this kernel"))
+        };
+
+        let mut maps = Vec::new();
+        for line in numa_maps.lines() {
+            maps.push(try!(FromStr::from_str(line)));
+        }
+        return Ok(maps);
+    }
+
+    /// Read the memory mappings that are both writable and executable.
+    ///
+    /// Few programs need these other than JIT compilers, so they are a common
+    /// sign of injected code for intrusion detection to look for.
+    pub fn writable_executable_maps(&self) -> Result<Vec<MemoryMap>> {
+        self.memory_maps_matching(|perms| perms.write && perms.execute)
+    }
+
+    /// Read `/proc/[pid]/mountinfo` as a vector of mounts.
+    ///
+    /// This lists the mounts in the mount namespace of the process, so for a
+    /// process in a container it shows the container's mounts rather than
+    /// those of the host.
+    pub fn mounts(&self) -> Result<Vec<MountInfo>> {
+        procfs_with(self.pid, "mountinfo", |mountinfo| {
+            mountinfo.lines().map(FromStr::from_str).collect()
+        })
+    }
+
+    /// Read the memory usage and limits of the cgroup the process is in.
+    ///
+    /// The cgroup v2 directory is used if it has the memory controller
+    /// enabled, and otherwise this falls back to the cgroup v1 `memory`
+    /// hierarchy. Returns an error with the kind `Other` if neither can be
+    /// found, e.g. when the cgroup isn't visible from this namespace.
+    pub fn cgroup_memory(&self) -> Result<CgroupMemory> {
+        if let Some(dir) = try!(cgroup_dir(self.pid, None)) {
+            if let Some(current) = try!(read_cgroup_value(&dir, "memory.current")) {
+                return Ok(CgroupMemory {
+                    current:        current,
+                    max:            cgroup_limit(try!(read_cgroup_value(&dir, "memory.max"))),
+                    high:           cgroup_limit(try!(read_cgroup_value(&dir, "memory.high"))),
+                    swap_current:   try!(read_cgroup_value(&dir, "memory.swap.current"))
+                });
+            }
+        }
+
+        if let Some(dir) = try!(cgroup_dir(self.pid, Some("memory"))) {
+            if let Some(current) = try!(read_cgroup_value(&dir, "memory.usage_in_bytes")) {
+                let memsw = try!(read_cgroup_value(&dir, "memory.memsw.usage_in_bytes"));
+                return Ok(CgroupMemory {
+                    current:        current,
+                    max:            cgroup_limit(try!(read_cgroup_value(&dir, "memory.limit_in_bytes"))),
+                    high:           None,
+                    swap_current:   memsw.map(|memsw| memsw.saturating_sub(current))
+                });
+            }
+        }
+
+        return Err(Error::new(ErrorKind::Other,
+            format!("Could not find the memory cgroup of process {}", self.pid)));
+    }
+
+    /// Read the CPU usage and throttling of the cgroup the process is in.
+    ///
+    /// The cgroup is found in the same way as for `cgroup_memory()`. On cgroup
+    /// v2 this reads `cpu.stat`, and otherwise falls back to `cpu.stat` from
+    /// the cgroup v1 `cpu` hierarchy (where throttling is reported as
+    /// `throttled_time` in nanoseconds) and `cpuacct.usage` from the `cpuacct`
+    /// hierarchy, which are usually mounted together as `cpu,cpuacct`.
+    /// Returns an error with the kind `Other` if neither can be found.
+    pub fn cgroup_cpu_stat(&self) -> Result<CgroupCpuStat> {
+        if let Some(dir) = try!(cgroup_dir(self.pid, None)) {
+            match read_file(&dir.join("cpu.stat")) {
+                Ok(contents) => return FromStr::from_str(&contents),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e)
+            }
+        }
+
+        let usage = match try!(cgroup_dir(self.pid, Some("cpuacct"))) {
+            Some(dir) => try!(read_cgroup_value(&dir, "cpuacct.usage")),
+            None => None
+        };
+
+        if let Some(usage) = usage {
+            let mut stat = CgroupCpuStat { usage_usec: usage / 1000, ..Default::default() };
+
+            if let Some(dir) = try!(cgroup_dir(self.pid, Some("cpu"))) {
+                let contents = try!(read_file(&dir.join("cpu.stat")));
+                let fields = try!(parse_cgroup_keyed(&contents, "cpu.stat"));
+                let field = |key: &str| fields.get(key).cloned().unwrap_or(0);
+
+                stat.nr_periods = field("nr_periods");
+                stat.nr_throttled = field("nr_throttled");
+                stat.throttled_usec = field("throttled_time") / 1000;
+            }
+
+            return Ok(stat);
+        }
+
+        return Err(Error::new(ErrorKind::Other,
+            format!("Could not find the cpu cgroup of process {}", self.pid)));
+    }
+
+    /// Read each thread of the process from `/proc/[pid]/task`.
+    ///
+    /// Threads that exit while the directory is being read are skipped.
+    pub fn threads(&self) -> Result<Vec<Thread>> {
+        let mut threads = Vec::new();
+
+        let dir = procfs_path(self.pid, "task");
+        for entry in try!(read_dir(dir).map_err(|e| Error::for_process(self.pid, e))) {
+            let entry = try!(entry.map_err(|e| Error::for_process(self.pid, e)));
+            let tid = match FromStr::from_str(&entry.file_name().to_string_lossy()) {
+                Ok(tid) => tid,
+                Err(_)  => continue
+            };
+
+            match Thread::new(self.pid, tid) {
+                Ok(thread) => threads.push(thread),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e)
+            }
+        }
+
+        return Ok(threads);
+    }
+
+    /// Return the direct children of the process.
+    ///
+    /// Processes that exit while `/proc` is being scanned are skipped.
+    pub fn children(&self) -> Result<Vec<Process>> {
+        Ok(try!(scan()).into_iter().filter(|p| p.ppid == self.pid).collect())
+    }
+
+    /// Return every descendant of the process.
+    ///
+    /// Descendants are found from a single scan of `/proc`, and are ordered so
+    /// that each process comes before any of its own children.
+    pub fn descendants(&self) -> Result<Vec<Process>> {
+        let processes = try!(scan());
+        let mut descendants: Vec<Process> = Vec::new();
+        let mut parents = vec![self.pid];
+
+        // Each PID is only visited once, in case PID reuse has created a loop
+        while !parents.is_empty() {
+            let mut children = Vec::new();
+            for process in processes.iter() {
+                if parents.contains(&process.ppid)
+                    && process.pid != self.pid
+                    && !descendants.iter().any(|d| d.pid == process.pid) {
+                    children.push(process.clone());
+                }
+            }
+
+            parents = children.iter().map(|c| c.pid).collect();
+            descendants.extend(children);
+        }
+
+        return Ok(descendants);
+    }
+
+    /// Return the memory usage of the process and all of its descendants.
+    ///
+    /// Each field is summed across the tree. Pages shared between processes,
+    /// such as shared libraries and memory shared by a parent with the
+    /// children it forked, are counted once for each process that maps them,
+    /// so the total `resident` size can be much larger than the memory the
+    /// tree actually uses. Prefer `memory_full_tree` where PSS or USS can be
+    /// read. Descendants that exit while the tree is read are skipped.
+    pub fn memory_tree(&self) -> Result<Memory> {
+        let mut total = try!(self.memory());
+
+        for process in try!(self.descendants()) {
+            let memory = match process.memory() {
+                Ok(memory) => memory,
+                Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e)
+            };
+
+            total.size += memory.size;
+            total.resident += memory.resident;
+            total.share += memory.share;
+            total.text += memory.text;
+            total.lib += memory.lib;
+            total.data += memory.data;
+            total.dt += memory.dt;
+        }
+
+        return Ok(total);
+    }
+
+    /// Return the detailed memory usage of the process and all of its
+    /// descendants.
+    ///
+    /// Unlike the `resident` size summed by `memory_tree`, the PSS of each
+    /// process only includes its share of each shared page, so the total PSS
+    /// doesn't count shared libraries more than once. The total USS is the
+    /// memory that would be freed if the whole tree exited, not counting pages
+    /// shared between processes in it. Descendants that exit while the tree is
+    /// read are skipped.
+    pub fn memory_full_tree(&self) -> Result<MemoryFull> {
+        let mut total = try!(self.memory_full_rollup());
+
+        for process in try!(self.descendants()) {
+            let memory = match process.memory_full_rollup() {
+                Ok(memory) => memory,
+                Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e)
+            };
+
+            total.rss += memory.rss;
+            total.pss += memory.pss;
+            total.uss += memory.uss;
+            total.shared_clean += memory.shared_clean;
+            total.shared_dirty += memory.shared_dirty;
+            total.private_clean += memory.private_clean;
+            total.private_dirty += memory.private_dirty;
+            total.swap += memory.swap;
+        }
+
+        return Ok(total);
+    }
+
+    /// Send a signal to the process.
+    ///
+    /// Returns `Ok(true)` if the signal was sent, and `Ok(false)` if the
+    /// process no longer exists - including when its PID has been reused by
+    /// another process, unless `set_identity_check` has disabled that check.
+    ///
+    /// `kill(2)` treats a PID of 0 or less as a process group, so those PIDs
+    /// are rejected with an `InvalidInput` error rather than signalling more
+    /// than one process. Use `signal_process_group` to signal a whole group.
+    pub fn send_signal(&self, signal: Signal) -> Result<bool> {
+        // Non-positive PIDs are left for `signal_pid` to reject
+        if self.pid > 0 {
+            match self.check_identity() {
+                Ok(()) => (),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+                Err(e) => return Err(e)
+            }
+        }
+
+        signal_pid(self.pid, signal)
+    }
+
+    /// Send SIGKILL to the process.
+    ///
+    /// Returns `Ok(false)` if the process no longer exists, see `send_signal`.
+    pub fn kill(&self) -> Result<bool> {
+        self.send_signal(Signal::Kill)
+    }
+
+    /// Send a signal to the process and all of its descendants, parent first.
+    ///
+    /// See `kill_tree_with` for details.
+    pub fn kill_tree(&self, signal: Signal) -> Result<Vec<PID>> {
+        self.kill_tree_with(signal, KillOrder::ParentFirst)
+    }
+
+    /// Send a signal to the process and all of its descendants.
+    ///
+    /// The tree is read before any signals are sent, and each process in it is
+    /// signalled even if it has since been reparented. Children created after
+    /// the tree was read are not signalled - `KillOrder` describes how each
+    /// ordering affects that race.
+    ///
+    /// Returns the PIDs that were signalled. Processes that exited before they
+    /// could be signalled are left out. If a process can't be signalled for
+    /// any other reason, such as a `PermissionDenied` error for a descendant
+    /// owned by another user, the rest of the tree is still signalled and the
+    /// first of those errors is returned once every process has been tried.
+    /// Use `signal_many` with the PIDs of `descendants` to get the result for
+    /// each process instead.
+    pub fn kill_tree_with(&self, signal: Signal, order: KillOrder) -> Result<Vec<PID>> {
+        let mut targets = vec![self.clone()];
+        targets.extend(try!(self.descendants()));
+
+        if let KillOrder::LeavesFirst = order {
+            targets.reverse();
+        }
+
+        let mut signalled = Vec::new();
+        let mut error = None;
+        for target in targets.iter() {
+            match target.send_signal(signal) {
+                Ok(true) => signalled.push(target.pid),
+                Ok(false) => (),
+                Err(e) => if error.is_none() { error = Some(e) }
+            }
+        }
+
+        return match error {
+            Some(e) => Err(e),
+            None => Ok(signalled)
+        };
+    }
+}
+
+impl PartialEq for Process {
+    // Compares processes using their PID and starttime as an indentity
+    fn eq(&self, other: &Process) -> bool {
+        self.same_identity(other)
+    }
+}
+
+impl Eq for Process {}
+
+impl Hash for Process {
+    // Hashes the same identity that `eq` compares
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pid.hash(state);
+        self.starttime.hash(state);
+    }
+}
+
+/// The difference between two snapshots of the processes on the system
+///
+/// Processes are matched by their PID and start time, so a PID that was
+/// reused between the snapshots is both an exited and a started process.
+#[derive(Clone,Debug,Default)]
+pub struct ProcessDiff {
+    /// Processes only in the new snapshot
+    pub started: Vec<Process>,
+
+    /// Processes only in the old snapshot
+    pub exited: Vec<Process>,
+
+    /// Processes in both snapshots, as `(old, new)` pairs
+    pub persisted: Vec<(Process, Process)>
+}
+
+/// Compare two snapshots of the processes on the system, such as from `all`
+///
+/// Processes keep the order of the snapshot they were taken from, with
+/// `persisted` in the order of the new snapshot.
+pub fn diff(old: &[Process], new: &[Process]) -> ProcessDiff {
+    let old_set: HashMap<&Process, &Process> = old.iter().map(|p| (p, p)).collect();
+    let new_set: HashSet<&Process> = new.iter().collect();
+    let mut diff = ProcessDiff::default();
+
+    for process in new {
+        match old_set.get(process) {
+            Some(previous) => diff.persisted.push(((*previous).clone(), process.clone())),
+            None => diff.started.push(process.clone())
+        }
+    }
+
+    for process in old {
+        if !new_set.contains(process) {
+            diff.exited.push(process.clone());
+        }
+    }
+
+    return diff;
+}
+
+/// Rates of storage I/O by a process, in bytes per second
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct IoRates {
+    /// Bytes per second fetched from storage
+    pub read_bytes_per_sec: f64,
+
+    /// Bytes per second sent to storage
+    pub write_bytes_per_sec: f64
+}
+
+/// Measures the rate of storage I/O by a process between successive polls
+///
+/// This is the per-process view an `iotop`-like tool needs. Each call to
+/// `poll` compares `read_bytes` and `write_bytes` from `/proc/[pid]/io` with
+/// the previous poll.
+#[derive(Clone,Debug)]
+pub struct ProcessIoCollector {
+    process: Process,
+    previous: IoCounters,
+    time: Instant
+}
+
+impl ProcessIoCollector {
+    /// Start collecting the I/O rates of a process.
+    ///
+    /// Returns an error with the kind `Other` if the kernel was built without
+    /// I/O accounting.
+    pub fn new(pid: PID) -> Result<ProcessIoCollector> {
+        let process = try!(Process::new(pid));
+        let previous = try!(ProcessIoCollector::read(&process));
+
+        return Ok(ProcessIoCollector {
+            process: process,
+            previous: previous,
+            time: Instant::now()
+        });
+    }
+
+    fn read(process: &Process) -> Result<IoCounters> {
+        try!(process.io()).ok_or(Error::new(ErrorKind::Other,
+            "I/O accounting is not enabled in this kernel"))
+    }
+
+    /// Return the I/O rates since the previous poll, or since the collector
+    /// was created.
+    ///
+    /// Returns an error with the kind `NotFound` if the process has exited,
+    /// including when its PID has been reused by another process.
+    pub fn poll(&mut self) -> Result<IoRates> {
+        let exited = || Error::NoSuchProcess { pid: self.process.pid };
+
+        let current = match Process::new(self.process.pid) {
+            Ok(ref process) if process.same_identity(&self.process) => {
+                try!(ProcessIoCollector::read(process))
+            },
+            Ok(_) => return Err(exited()),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Err(exited()),
+            Err(e) => return Err(e)
+        };
+        let now = Instant::now();
+
+        let elapsed = now.duration_since(self.time);
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+        let rate = |after: u64, before: u64| if seconds > 0.0 {
+            after.saturating_sub(before) as f64 / seconds
+        } else {
+            0.0
+        };
+
+        let rates = IoRates {
+            read_bytes_per_sec: rate(current.read_bytes, self.previous.read_bytes),
+            write_bytes_per_sec: rate(current.write_bytes, self.previous.write_bytes)
+        };
+
+        self.previous = current;
+        self.time = now;
+        return Ok(rates);
+    }
+}
+
+/// Selects which files in `/proc/[pid]` a `ProcessInfo` is read from
+///
+/// `/proc/[pid]/stat` is always read, and each `with_*` method adds another
+/// file, so the cost of a snapshot is visible where it is built:
+///
+/// ```no_run
+/// use psutil::process::ProcessInfoBuilder;
+///
+/// let info = ProcessInfoBuilder::new().with_memory().with_cmdline().collect(1).unwrap();
+/// ```
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct ProcessInfoBuilder {
+    status: bool,
+    memory: bool,
+    io: bool,
+    cmdline: bool
+}
+
+impl ProcessInfoBuilder {
+    pub fn new() -> ProcessInfoBuilder {
+        ProcessInfoBuilder::default()
+    }
+
+    /// Also read `/proc/[pid]/status`
+    pub fn with_status(mut self) -> ProcessInfoBuilder {
+        self.status = true;
+        self
+    }
+
+    /// Also read `/proc/[pid]/statm`
+    pub fn with_memory(mut self) -> ProcessInfoBuilder {
+        self.memory = true;
+        self
+    }
+
+    /// Also read `/proc/[pid]/io`
+    pub fn with_io(mut self) -> ProcessInfoBuilder {
+        self.io = true;
+        self
+    }
+
+    /// Also read `/proc/[pid]/cmdline`
+    pub fn with_cmdline(mut self) -> ProcessInfoBuilder {
+        self.cmdline = true;
+        self
+    }
+
+    /// Read the selected files for a process.
+    ///
+    /// An error reading any of the selected files is returned, instead of a
+    /// partial snapshot.
+    pub fn collect(&self, pid: PID) -> Result<ProcessInfo> {
+        let process = try!(Process::new(pid));
+
+        let status = if self.status { Some(try!(process.status())) } else { None };
+        let memory = if self.memory { Some(try!(process.memory())) } else { None };
+        let io = if self.io { try!(process.io()) } else { None };
+        let cmdline = if self.cmdline { try!(process.cmdline_vec()) } else { None };
+
+        return Ok(ProcessInfo {
+            process: process,
+            status: status,
+            memory: memory,
+            io: io,
+            cmdline: cmdline
+        });
+    }
+}
+
+/// A snapshot of a process, read by `ProcessInfoBuilder::collect`
+///
+/// Each optional field is `None` if it wasn't selected, or if it doesn't
+/// apply to the process (such as the command line of a kernel thread).
+#[derive(Clone,Debug)]
+pub struct ProcessInfo {
+    /// The process as read from `/proc/[pid]/stat`
+    pub process: Process,
+
+    /// Read from `/proc/[pid]/status`
+    pub status: Option<Status>,
+
+    /// Read from `/proc/[pid]/statm`
+    pub memory: Option<Memory>,
+
+    /// Read from `/proc/[pid]/io`
+    pub io: Option<IoCounters>,
+
+    /// Read from `/proc/[pid]/cmdline`
+    pub cmdline: Option<Vec<String>>
+}
+
+/// A snapshot of a process where each optional file was read independently,
+/// returned by `Process::collect_lenient`
+///
+/// Each section holds the result of reading its file, so one that fails (for
+/// example with `PermissionDenied` on another user's `io`) doesn't stop the
+/// others from being shown.
+#[derive(Debug)]
+pub struct LenientProcess {
+    /// The process as read from `/proc/[pid]/stat`
+    pub process: Process,
+
+    /// Read from `/proc/[pid]/status`
+    pub status: Result<Status>,
+
+    /// Read from `/proc/[pid]/statm`
+    pub memory: Result<Memory>,
+
+    /// Read from `/proc/[pid]/smaps_rollup`, see `Process::memory_full_rollup`
+    pub smaps: Result<MemoryFull>,
+
+    /// Read from `/proc/[pid]/io`
+    pub io: Result<Option<IoCounters>>,
+
+    /// Read from `/proc/[pid]/cmdline`
+    pub cmdline: Result<Option<Vec<String>>>
+}
+
+/// The common health signals of a process, returned by `Process::health`
+#[derive(Clone,Debug)]
+pub struct ProcessHealth {
+    /// The process as read at the end of the CPU usage interval
+    pub process: Process,
+
+    /// State of the process
+    pub state: State,
+
+    /// Percentage of a CPU used over the interval, as `Process::cpu_percent`
+    pub cpu_percent: f64,
+
+    /// Resident set size (bytes)
+    pub rss: u64,
+
+    /// Number of threads
+    pub num_threads: i64,
+
+    /// Number of open file descriptors, or `None` if they can't be read
+    pub num_fds: Option<usize>,
+
+    /// Soft limit on the number of open file descriptors, or `None` if it
+    /// can't be read or is unlimited
+    pub fd_limit: Option<u64>,
+
+    /// `num_fds` as a fraction of `fd_limit`, where 1.0 means the process
+    /// can't open any more files
+    pub fd_usage: Option<f64>
+}
+
+/// Remembers the peak memory usage seen across successive samples of a process
+///
+/// Unlike `VmHWM` and `VmPeak` from `/proc/[pid]/status`, the peaks only cover
+/// the samples given to `update`, so they follow whatever cadence the caller
+/// samples at and are reset by creating a new tracker.
+#[derive(Clone,Debug)]
+pub struct PeakTracker {
+    page_size: u64,
+    peak_rss: u64,
+    peak_rss_time: Option<SystemTime>,
+    peak_vsz: u64,
+    peak_vsz_time: Option<SystemTime>
+}
+
+impl PeakTracker {
+    pub fn new() -> PeakTracker {
+        PeakTracker::with_page_size(page_size())
+    }
+
+    /// Create a tracker converting `Process::rss` to bytes with `page_size`
+    /// rather than the page size of this system.
+    pub fn with_page_size(page_size: u64) -> PeakTracker {
+        PeakTracker {
+            page_size: page_size,
+            peak_rss: 0,
+            peak_rss_time: None,
+            peak_vsz: 0,
+            peak_vsz_time: None
+        }
+    }
+
+    /// Record a sample of a process, timestamped with the current time
+    pub fn update(&mut self, process: &Process) {
+        self.update_at(process, SystemTime::now())
+    }
+
+    /// Record a sample of a process that was taken at the given time
+    pub fn update_at(&mut self, process: &Process, time: SystemTime) {
+        let rss = process.rss as u64 * self.page_size;
+
+        if self.peak_rss_time.is_none() || rss > self.peak_rss {
+            self.peak_rss = rss;
+            self.peak_rss_time = Some(time);
+        }
+
+        if self.peak_vsz_time.is_none() || process.vsize > self.peak_vsz {
+            self.peak_vsz = process.vsize;
+            self.peak_vsz_time = Some(time);
+        }
+    }
+
+    /// Largest Resident Set Size seen (bytes)
+    pub fn peak_rss(&self) -> u64 {
+        self.peak_rss
+    }
+
+    /// Time of the sample with the largest Resident Set Size
+    pub fn peak_rss_time(&self) -> Option<SystemTime> {
+        self.peak_rss_time
+    }
+
+    /// Largest virtual memory size seen (bytes)
+    pub fn peak_vsz(&self) -> u64 {
+        self.peak_vsz
+    }
+
+    /// Time of the sample with the largest virtual memory size
+    pub fn peak_vsz_time(&self) -> Option<SystemTime> {
+        self.peak_vsz_time
+    }
+}
+
+impl Default for PeakTracker {
+    fn default() -> PeakTracker {
+        PeakTracker::new()
+    }
+}
+
+/// Return the PID of every process in /proc
+///
+/// This only lists the numeric entries of `/proc`, and does not read anything
+/// about the processes themselves.
+pub fn pids() -> Result<Vec<PID>> {
+    let mut pids = Vec::new();
+
+    for entry in try!(read_dir(&Path::new("/proc"))) {
+        let entry = try!(entry);
+        match FromStr::from_str(&entry.file_name().to_string_lossy()) {
+            Ok(pid) => pids.push(pid),
+            Err(_)  => ()
         }
+    }
 
-        // This is 'safe' to call as sysconf should only return an error for
-        // invalid inputs, or options and limits (which _SC_CLK_TCK is not).
-        let ticks_per_second: f64 = unsafe { sysconf(_SC_CLK_TCK) } as f64;
+    return Ok(pids);
+}
 
-        // Read each field into an attribute for a new Process instance
-        return Ok(Process {
-            pid:                    from_str!(stat[00]),
-            uid:                    meta.uid(),
-            gid:                    meta.gid(),
-            comm:         Process::parse_comm(stat[01]),
-            state:                  from_str!(stat[02]),
-            ppid:                   from_str!(stat[03]),
-            pgrp:                   from_str!(stat[04]),
-            session:                from_str!(stat[05]),
-            tty_nr:                 from_str!(stat[06]),
-            tpgid:                  from_str!(stat[07]),
-            flags:                  from_str!(stat[08]),
-            minflt:                 from_str!(stat[09]),
-            cminflt:                from_str!(stat[10]),
-            majflt:                 from_str!(stat[11]),
-            cmajflt:                from_str!(stat[12]),
-            utime:                  u64::from_str(stat[13]).unwrap() as f64 / ticks_per_second,
-            stime:                  u64::from_str(stat[14]).unwrap() as f64 / ticks_per_second,
-            cutime:                 i64::from_str(stat[15]).unwrap() as f64 / ticks_per_second,
-            cstime:                 i64::from_str(stat[16]).unwrap() as f64 / ticks_per_second,
-            priority:               from_str!(stat[17]),
-            nice:                   from_str!(stat[18]),
-            num_threads:            from_str!(stat[19]),
-            // itrealvalue:         from_str!(stat[20]),
-            starttime:              from_str!(stat[21]),
-            vsize:                  from_str!(stat[22]),
-            rss:                    from_str!(stat[23]),
-            rsslim:                 from_str!(stat[24]),
-            startcode:              from_str!(stat[25]),
-            endcode:                from_str!(stat[26]),
-            startstack:             from_str!(stat[27]),
-            kstkesp:                from_str!(stat[28]),
-            kstkeip:                from_str!(stat[29]),
-            // signal:              from_str!(stat[30]),
-            // blocked:             from_str!(stat[31]),
-            // sigignore:           from_str!(stat[32]),
-            // sigcatch:            from_str!(stat[33]),
-            wchan:                  from_str!(stat[34]),
-            // nswap:               from_str!(stat[35]),
-            // cnswap:              from_str!(stat[36]),
-            exit_signal:            from_str!(stat[37]),
-            processor:              from_str!(stat[38]),
-            rt_priority:            from_str!(stat[39]),
-            policy:                 from_str!(stat[40]),
-            delayacct_blkio_ticks:  from_str!(stat[41]),
-            guest_time:             u64::from_str(stat[42]).unwrap() as f64 / ticks_per_second,
-            cguest_time:            i64::from_str(stat[43]).unwrap() as f64 / ticks_per_second,
-            start_data:             from_str!(stat[44]),
-            end_data:               from_str!(stat[45]),
-            start_brk:              from_str!(stat[46]),
-            arg_start:              from_str!(stat[47]),
-            arg_end:                from_str!(stat[48]),
-            env_start:              from_str!(stat[49]),
-            env_end:                from_str!(stat[50]),
-            exit_code:              from_str!(stat[51])
-        });
+/// Return the number of processes in /proc
+pub fn pid_count() -> Result<usize> {
+    Ok(try!(pids()).len())
+}
+
+/// Read the target of the `/proc/self/exe` link
+///
+/// This describes the calling process without looking up its PID.
+pub fn self_exe() -> Result<PathBuf> {
+    fs::read_link("/proc/self/exe").map_err(Error::from)
+}
+
+/// Read the target of the `/proc/self/cwd` link
+pub fn self_cwd() -> Result<PathBuf> {
+    fs::read_link("/proc/self/cwd").map_err(Error::from)
+}
+
+/// Read `/proc/self/cmdline` as a String, in the same way as `Process::cmdline`
+///
+/// The calling process is read from `/proc/self/stat`, so that the command
+/// line is cut at the end of its argument area as in `args_from_cmdline`.
+pub fn self_cmdline() -> Result<Option<String>> {
+    let path = Path::new("/proc/self/stat");
+    let meta = try!(fs::metadata(path));
+    let stat = try!(read_file_bytes(path));
+    let process = try!(Process::from_stat(&String::from_utf8_lossy(&stat),
+        meta.uid(), meta.gid(), ticks_per_second()));
+
+    let cmdline = try!(read_file_bytes(&Path::new("/proc/self/cmdline")));
+    Ok(process.args_from_cmdline(&cmdline).map(|c| c.join(" ")))
+}
+
+/// Set the name of the calling thread, as shown in `/proc/[pid]/task/[tid]/comm`.
+///
+/// This uses `prctl(PR_SET_NAME)`, so it only renames the thread that calls
+/// it - naming the main thread also changes the name returned by
+/// `Process::comm_live`. Names are limited to 15 bytes by the kernel, and
+/// longer names or names containing a NUL byte return an `InvalidInput` error
+/// rather than being truncated.
+pub fn set_thread_name(name: &str) -> Result<()> {
+    if name.len() >= TASK_COMM_LEN {
+        return Err(Error::new(ErrorKind::InvalidInput, format!(
+            "Thread name {:?} is longer than {} bytes", name, TASK_COMM_LEN - 1)));
     }
 
-    /// Create a Process by reading it's PID from a pidfile.
-    pub fn from_pidfile(path: &Path) -> Result<Process> {
-        Process::new(try!(read_pidfile(&path)))
+    let cname = try!(CString::new(name).map_err(|_| {
+        Error::new(ErrorKind::InvalidInput, "Thread name contains a NUL byte")
+    }));
+
+    return match unsafe { prctl(PR_SET_NAME, cname.as_ptr()) } {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error())
+    };
+}
+
+/// Share the energy used between two readings of the RAPL counters, in joules
+///
+/// This is the arithmetic of `Process::cpu_energy_estimate`. `before` and
+/// `after` are the `energy_uj` and `max_energy_range_uj` of each package, in
+/// the same order, and a counter that is lower in `after` is taken to have
+/// wrapped around once. The process is given the fraction `process_time`
+/// out of `busy_time` (both in seconds) of the total, clamped to `0..1`, and
+/// nothing if no CPU time was used at all.
+pub fn cpu_energy_share(before: &[(u64, u64)], after: &[(u64, u64)],
+                        process_time: f64, busy_time: f64) -> f64 {
+    if busy_time <= 0.0 {
+        return 0.0;
     }
 
-    /// Return `true` if the process was alive at the time it was read.
-    pub fn is_alive(&self) -> bool {
-        match self.state {
-            State::Zombie => false,
-            _ => true
+    let mut microjoules = 0;
+    for (before, after) in before.iter().zip(after.iter()) {
+        // The counters wrap around once they reach `max_energy_range_uj`
+        microjoules += if after.0 >= before.0 {
+            after.0 - before.0
+        } else {
+            after.0 + after.1 - before.0
+        };
+    }
+
+    let share = (process_time / busy_time).max(0.0).min(1.0);
+    return microjoules as f64 / 1_000_000.0 * share;
+}
+
+/// Read the energy counter and its range for each RAPL package, in microjoules
+///
+/// Only top level `intel-rapl:N` zones are read, as the `intel-rapl:N:M`
+/// subzones are already counted by their package.
+fn rapl_energy() -> Result<Vec<(u64, u64)>> {
+    let mut zones = Vec::new();
+
+    let entries = match read_dir("/sys/class/powercap") {
+        Ok(entries) => entries,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => {
+            return Err(Error::new(ErrorKind::NotFound, "RAPL is not available"));
+        },
+        Err(e) => return Err(Error::from(e))
+    };
+
+    for entry in entries {
+        let path = try!(entry).path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        if name.starts_with("intel-rapl:") && name.matches(':').count() == 1 {
+            zones.push(path);
         }
     }
 
-    /// Read `/proc/[pid]/cmdline` as a vector.
-    ///
-    /// Returns `Err` if `/proc/[pid]/cmdline` is empty.
-    pub fn cmdline_vec(&self) -> Result<Option<Vec<String>>> {
-        let cmdline = try!(procfs(self.pid, "cmdline"));
+    if zones.is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, "RAPL is not available"));
+    }
 
-        if cmdline == "" {
-            return Ok(None);
-        } else {
-            // Split terminator skips empty trailing substrings
-            let split = cmdline.split_terminator(
-                |c: char| c == '\0' || c == ' ');
+    // Sort so that zones match up between calls
+    zones.sort();
+
+    let mut energy = Vec::new();
+    for zone in zones {
+        let read = |name: &str| -> Result<u64> {
+            try!(read_file(&zone.join(name))).trim().parse().map_err(|_| {
+                Error::new(ErrorKind::Other, "Could not parse RAPL energy counter")
+            })
+        };
+        energy.push((try!(read("energy_uj")), try!(read("max_energy_range_uj"))));
+    }
 
-            // `split` returns a vector of slices viewing `cmdline`, so they
-            // get mapped to actuall strings before being returned as a vector.
-            return Ok(Some(split.map(|x| x.to_string()).collect()));
+    return Ok(energy);
+}
+
+/// Send a signal with `kill(2)`, returning `false` if no process was found
+fn kill(pid: PID, signal: Signal) -> Result<bool> {
+    use libc::funcs::posix88::signal::kill;
+
+    return match unsafe { kill(pid, signal.number()) } {
+        0  => Ok(true),
+        -1 => match Error::last_os_error() {
+            ref e if e.raw_os_error() == Some(ESRCH) => Ok(false),
+            e => Err(e)
+        },
+        _  => unreachable!()
+    };
+}
+
+/// Send a signal to a single process, refusing PIDs `kill(2)` treats as groups
+fn signal_pid(pid: PID, signal: Signal) -> Result<bool> {
+    if pid <= 0 {
+        return Err(Error::new(ErrorKind::InvalidInput,
+            "Refusing to signal a PID that is not positive"));
+    }
+
+    kill(pid, signal).map_err(|e| Error::for_process(pid, e))
+}
+
+/// Send a signal to each of several processes, returning the result for each
+///
+/// Each result is the same as from `Process::send_signal`: `Ok(true)` if the
+/// signal was sent and `Ok(false)` if the process was already gone. An error
+/// for one PID, such as `PermissionDenied`, doesn't stop the others from
+/// being signalled. The results are in the same order as `pids`.
+pub fn signal_many(pids: &[PID], signal: Signal) -> Vec<(PID, Result<bool>)> {
+    pids.iter().map(|&pid| (pid, signal_pid(pid, signal))).collect()
+}
+
+/// Send a signal to every process in a process group
+///
+/// Returns `Ok(false)` if there was no such process group, and an
+/// `InvalidInput` error if `pgid` is not positive.
+pub fn signal_process_group(pgid: PID, signal: Signal) -> Result<bool> {
+    if pgid <= 0 {
+        return Err(Error::new(ErrorKind::InvalidInput,
+            "Refusing to signal a process group ID that is not positive"));
+    }
+
+    kill(-pgid, signal)
+}
+
+/// Search a directory (but not its subdirectories) for a character device
+///
+/// Returns the name of the device relative to `/dev`. Entries that can't be
+/// read are skipped.
+fn find_device(dir: &Path, major: u32, minor: u32) -> Result<Option<String>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    for entry in try!(read_dir(dir)) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue
+        };
+
+        match entry.metadata() {
+            Ok(ref meta) if meta.file_type().is_char_device() => {
+                let rdev = meta.rdev();
+                let (rdev_major, rdev_minor) = (((rdev >> 8) & 0xfff) as u32,
+                    ((rdev & 0xff) | ((rdev >> 12) & 0xfff00)) as u32);
+                if (rdev_major, rdev_minor) == (major, minor) {
+                    let path = entry.path();
+                    let name = path.strip_prefix("/dev").unwrap_or(&path);
+                    return Ok(Some(name.to_string_lossy().into_owned()));
+                }
+            },
+            _ => ()
         }
     }
 
-    /// Return the result of `cmdline_vec` as a String.
-    pub fn cmdline(&self) -> Result<Option<String>> {
-        Ok(try!(self.cmdline_vec()).and_then(|c| Some(c.join(" "))))
+    return Ok(None);
+}
+
+/// How often the thread spawned by `watch` checks the process
+const WATCH_INTERVAL_MS: u64 = 100;
+
+/// Sent by `watch` when the watched process exits
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct ExitEvent {
+    /// PID of the process that exited
+    pub pid: PID,
+
+    /// When the exit was noticed, which is up to 100 milliseconds after it
+    /// happened
+    pub noticed: SystemTime
+}
+
+/// Receives an `ExitEvent` when a watched process exits
+///
+/// This dereferences to the `Receiver`, so it can be used with `recv`,
+/// `try_recv` and `recv_timeout`. Dropping it cancels the watch, and the
+/// watching thread stops the next time it wakes up.
+#[derive(Debug)]
+pub struct Watch {
+    receiver: Receiver<ExitEvent>,
+    cancelled: Arc<AtomicBool>
+}
+
+impl Deref for Watch {
+    type Target = Receiver<ExitEvent>;
+
+    fn deref(&self) -> &Receiver<ExitEvent> {
+        &self.receiver
     }
+}
 
-    /// Reads `/proc/[pid]/statm` into a struct.
-    pub fn memory(&self) -> Result<Memory> {
-        Memory::new(self.pid)
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, AtomicOrdering::SeqCst);
     }
+}
 
-    /// Send SIGKILL to the process.
-    pub fn kill(&self) -> Result<()> {
-        use libc::funcs::posix88::signal::kill;
-        use libc::consts::os::posix88::SIGKILL;
+/// Watch a process, receiving an event when it exits
+///
+/// This spawns a thread for each watched process, which reads
+/// `/proc/[pid]/stat` every 100 milliseconds until the process exits or the
+/// watch is dropped. A process has exited once it is a zombie or its PID no
+/// longer belongs to the same process, and an event is sent straight away if
+/// there is no process with the PID when the watch starts.
+pub fn watch(pid: PID) -> Watch {
+    let (sender, receiver) = channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let thread_cancelled = cancelled.clone();
+    let watched = Process::new(pid).ok();
+
+    thread::spawn(move || {
+        while !thread_cancelled.load(AtomicOrdering::SeqCst) {
+            let alive = match (&watched, Process::new(pid)) {
+                (&Some(ref watched), Ok(ref current)) => {
+                    current.same_identity(watched) && current.is_alive()
+                },
+                _ => false
+            };
+
+            if !alive {
+                let _ = sender.send(ExitEvent { pid: pid, noticed: SystemTime::now() });
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(WATCH_INTERVAL_MS));
+        }
+    });
+
+    return Watch { receiver: receiver, cancelled: cancelled };
+}
+
+/// Format a time as `YYYY-MM-DD HH:MM:SS UTC`
+///
+/// The date is calculated from days since the epoch using the proleptic
+/// Gregorian calendar. Times before the epoch are shown as the epoch.
+fn format_utc(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, time) = (seconds / 86400, seconds % 86400);
+
+    // Shift the epoch to 0000-03-01, so that leap days end each 400 year era
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    return format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60);
+}
+
+/// Read several processes, returning the result for each PID in order
+///
+/// An error reading one process doesn't affect the others, so a PID that has
+/// exited has a `NotFound` error alongside the processes that were read. The
+/// same buffer is used to read each `/proc/[pid]/stat` file.
+pub fn new_many(pids: &[PID]) -> Vec<(PID, Result<Process>)> {
+    let mut buffer = Vec::new();
+    let ticks = ticks_per_second();
+
+    return pids.iter().map(|&pid| (pid, read_stat_into(pid, &mut buffer, ticks))).collect();
+}
+
+/// Read a file in `/proc/[pid]` into an existing buffer, see `read_whole_into`
+///
+/// Scans use this to read every process into one buffer rather than
+/// allocating a new one for each file. Transient errors are retried as for
+/// `read_file`. Returns the open file, and the contents borrowed from the
+/// buffer.
+fn read_into<'a>(pid: PID, name: &str, buffer: &'a mut Vec<u8>) -> Result<(File, &'a [u8])> {
+    let path = procfs_path(pid, name);
+    let (file, len) = try!(retry(read_policy(), || read_whole_into(&path, buffer))
+        .map_err(|e| Error::for_process(pid, e)));
+    return Ok((file, &buffer[..len]));
+}
+
+/// Read a process from `/proc/[pid]/stat` using an existing buffer
+///
+/// The name is decoded lossily, as in `Process::new`, so that a process
+/// naming itself with invalid UTF-8 can't stop a scan.
+fn read_stat_into(pid: PID, buffer: &mut Vec<u8>, ticks: u64) -> Result<Process> {
+    let (file, stat) = try!(read_into(pid, "stat", buffer));
+    let meta = try!(file.metadata().map_err(|e| Error::for_process(pid, e)));
+
+    Process::from_stat(&String::from_utf8_lossy(stat), meta.uid(), meta.gid(), ticks)
+}
+
+/// Read the memory of a process from `/proc/[pid]/statm` using an existing buffer
+fn read_statm_into(pid: PID, buffer: &mut Vec<u8>, page_size: u64) -> Result<Memory> {
+    let (_, statm) = try!(read_into(pid, "statm", buffer));
+    let statm = try!(str::from_utf8(statm).map_err(|_| {
+        Error::new(ErrorKind::InvalidData, format!("/proc/{}/statm is not valid UTF-8", pid))
+    }));
+    Memory::from_statm(statm, page_size)
+}
+
+/// Read every process and its memory sizes as cheaply as possible
+///
+/// Only `/proc/[pid]/stat` and `/proc/[pid]/statm` are read, each in a single
+/// read into one reused buffer, which is fast enough to scan thousands of
+/// processes in a few milliseconds - see `examples/scan.rs`. Nothing else is
+/// touched: `status` is parsed line by line and `smaps` has to walk every
+/// mapping of the process in the kernel, so fields like PSS and swap usage
+/// should be read with `memory_full_rollup` only for the processes that need
+/// them. Processes that exit during the scan are skipped.
+pub fn all_basic() -> Result<Vec<(Process, Memory)>> {
+    let mut processes = Vec::new();
+    let mut buffer = Vec::new();
+    let ticks = ticks_per_second();
+    let page_size = page_size();
 
-        return match unsafe { kill(self.pid, SIGKILL) } {
-            0  => Ok(()),
-            -1 => Err(Error::last_os_error()),
-            _  => unreachable!()
+    for pid in try!(pids()) {
+        let process = match read_stat_into(pid, &mut buffer, ticks) {
+            Ok(process) => process,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e)
         };
+
+        let memory = match process.state {
+            State::Zombie => Memory::default(),
+            _ => match read_statm_into(pid, &mut buffer, page_size) {
+                Ok(memory) => memory,
+                Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e)
+            }
+        };
+
+        processes.push((process, memory));
     }
+
+    return Ok(processes);
 }
 
-impl PartialEq for Process {
-    // Compares processes using their PID and starttime as an indentity
-    fn eq(&self, other: &Process) -> bool {
-        (self.pid == other.pid) && (self.starttime == other.starttime)
+/// Read every process in /proc, skipping any that can't be read
+///
+/// Every process is read into the same buffer. A process that exits during
+/// the scan, or whose `stat` can't be parsed, is skipped rather than failing
+/// the whole scan - `all_results` returns the error for each PID. Only an
+/// error listing `/proc` is returned.
+fn scan() -> Result<Vec<Process>> {
+    let mut buffer = Vec::new();
+    let ticks = ticks_per_second();
+
+    return Ok(try!(pids()).into_iter()
+        .filter_map(|pid| read_stat_into(pid, &mut buffer, ticks).ok())
+        .collect());
+}
+
+/// How long `top` and `Process::health` sample CPU usage for
+const SAMPLE_INTERVAL_MS: u64 = 100;
+
+/// Return the percentage of a CPU a process used between two samples
+fn cpu_percent_between(before: Option<&Process>, after: &Process, elapsed: Duration) -> f64 {
+    let after = after.cpu_times();
+
+    // A process that wasn't in the first sample started during the interval
+    let before = match before {
+        Some(before) => before.cpu_times(),
+        None => CpuTimes { ticks_per_second: after.ticks_per_second, ..CpuTimes::default() }
+    };
+    return after.percent_since(&before, elapsed);
+}
+
+/// Return the `limit` processes with the highest value of `sort`
+///
+/// Sorting by `SortKey::CpuPercent` scans every process twice and **blocks**
+/// for 100 milliseconds between the scans, as CPU usage can only be measured
+/// over an interval. The processes returned are from the second scan. Ties
+/// are broken by PID, and processes that exit during the scan are skipped.
+pub fn top(limit: usize, sort: SortKey) -> Result<Vec<Process>> {
+    let mut ranked: Vec<(f64, Process)> = match sort {
+        SortKey::CpuPercent => {
+            let start = Instant::now();
+            let before = try!(scan());
+            thread::sleep(Duration::from_millis(SAMPLE_INTERVAL_MS));
+            let elapsed = start.elapsed();
+            let after = try!(scan());
+
+            let before: HashMap<PID, &Process> = before.iter().map(|p| (p.pid, p)).collect();
+            after.into_iter().map(|process| {
+                let previous = before.get(&process.pid).cloned()
+                    .filter(|previous| previous.same_identity(&process));
+                (cpu_percent_between(previous, &process, elapsed), process)
+            }).collect()
+        },
+        SortKey::Rss => try!(scan()).into_iter().map(|p| (p.rss as f64, p)).collect(),
+        SortKey::Vsize => try!(scan()).into_iter().map(|p| (p.vsize as f64, p)).collect()
+    };
+
+    ranked.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal).then(a.1.pid.cmp(&b.1.pid))
+    });
+    ranked.truncate(limit);
+
+    return Ok(ranked.into_iter().map(|(_, process)| process).collect());
+}
+
+/// A process ranked by `top_by`, ordered by key and then by lowest PID
+struct Ranked<K: Ord> {
+    key: K,
+    process: Process
+}
+
+impl<K: Ord> PartialEq for Ranked<K> {
+    fn eq(&self, other: &Ranked<K>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord> Eq for Ranked<K> {}
+
+impl<K: Ord> PartialOrd for Ranked<K> {
+    fn partial_cmp(&self, other: &Ranked<K>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> Ord for Ranked<K> {
+    fn cmp(&self, other: &Ranked<K>) -> Ordering {
+        self.key.cmp(&other.key).then(other.process.pid.cmp(&self.process.pid))
+    }
+}
+
+/// Return the `limit` processes with the largest value of `key`, largest first.
+///
+/// Processes are read one at a time and only the best `limit` so far are
+/// kept, in a heap, so this uses little memory and avoids sorting every
+/// process on systems with very many of them. Processes with equal keys are
+/// ordered by PID, and processes that exit during the scan are skipped. Use
+/// `Reverse` for the smallest values instead, e.g.
+/// `top_by(5, |p| Reverse(p.nice))`.
+pub fn top_by<K: Ord, F: Fn(&Process) -> K>(limit: usize, key: F) -> Result<Vec<Process>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut heap = BinaryHeap::with_capacity(limit + 1);
+    let mut buffer = Vec::new();
+    let ticks = ticks_per_second();
+
+    for pid in try!(pids()) {
+        let process = match read_stat_into(pid, &mut buffer, ticks) {
+            Ok(process) => process,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e)
+        };
+
+        // The heap holds the smallest of the kept processes at the top
+        heap.push(Reverse(Ranked { key: key(&process), process: process }));
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+
+    return Ok(heap.into_sorted_vec().into_iter().map(|ranked| ranked.0.process).collect());
+}
+
+/// Return the most recently started process
+///
+/// Every process is read and compared by its start time, so the result is
+/// correct even after PIDs have wrapped around. Start times are in clock
+/// ticks, and processes started in the same tick are ordered by the higher
+/// PID. The kernel's last allocated PID, the final field of `/proc/loadavg`,
+/// is a much cheaper hint, but it may belong to a thread or a process that
+/// has already exited, and after wraparound it says nothing about order.
+/// Returns `None` only if every process exited during the scan.
+pub fn newest() -> Result<Option<Process>> {
+    Ok(try!(top_by(1, |process| (process.starttime, process.pid))).pop())
+}
+
+/// Return the processes started after `time`, oldest first
+///
+/// This needs no earlier snapshot of the process list. The boot time that
+/// start times are measured from is only known to the nearest second, so a
+/// process started within a second of `time` may be included or left out.
+/// Processes that exit during the scan are skipped.
+pub fn started_after(time: SystemTime) -> Result<Vec<Process>> {
+    let boot = try!(boot_time());
+    let mut processes: Vec<Process> = try!(scan()).into_iter()
+        .filter(|p| boot + ticks_to_duration(p.starttime, p.cpu_ticks.ticks_per_second) > time)
+        .collect();
+
+    processes.sort_by(|a, b| a.starttime.cmp(&b.starttime).then(a.pid.cmp(&b.pid)));
+    return Ok(processes);
+}
+
+/// Return every thread of every process, as shown by `top -H`
+///
+/// Threads and processes that exit during the scan are skipped.
+pub fn all_threads() -> Result<Vec<Thread>> {
+    let mut threads = Vec::new();
+
+    for process in try!(scan()) {
+        match process.threads() {
+            Ok(mut process_threads) => threads.append(&mut process_threads),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(e)
+        }
     }
+
+    return Ok(threads);
 }
 
 /// Return a vector of all processes in /proc
+///
+/// This panics if any process can't be read, including one that exits while
+/// `/proc` is being read. See `all_results` to handle those errors instead.
 pub fn all() -> Vec<Process> {
+    all_results().unwrap().into_iter().map(|(_, process)| process.unwrap()).collect()
+}
+
+/// Read every process in `/proc`, returning the result for each PID
+///
+/// Nothing is skipped: a process that exited after `/proc` was listed has a
+/// `NotFound` error, so callers can count, log or ignore failures as they
+/// choose. Only an error listing `/proc` itself is returned as an `Err`. See
+/// `new_many`, which this uses to read each process.
+pub fn all_results() -> Result<Vec<(PID, Result<Process>)>> {
+    Ok(new_many(&try!(pids())))
+}
+
+/// Return a vector of all processes owned by the given user
+///
+/// The owner of each `/proc/[pid]` directory is checked before the process is
+/// read, so processes belonging to other users are never parsed. Processes
+/// that exit during the scan are skipped.
+pub fn processes_of_user(uid: UID) -> Result<Vec<Process>> {
     let mut processes = Vec::new();
 
-    for entry in read_dir(&Path::new("/proc")).unwrap() {
-        let path = entry.unwrap().path();
-        let file_name = path.file_name().unwrap();
-        match FromStr::from_str(&file_name.to_string_lossy()) {
-            Ok(pid) => { processes.push(Process::new(pid).unwrap()) },
-            Err(_)  => ()
+    for entry in try!(read_dir(&Path::new("/proc"))) {
+        let entry = try!(entry);
+        let pid = match FromStr::from_str(&entry.file_name().to_string_lossy()) {
+            Ok(pid) => pid,
+            Err(_)  => continue
+        };
+
+        match entry.metadata() {
+            Ok(ref meta) if meta.uid() == uid => (),
+            Ok(_) => continue,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::for_process(pid, e))
+        }
+
+        match Process::new(pid) {
+            Ok(process) => if process.uid == uid { processes.push(process) },
+            Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(e)
+        }
+    }
+
+    return Ok(processes);
+}
+
+/// Return a vector of all processes with a command line containing `pattern`
+///
+/// The arguments are joined with spaces before matching, as with
+/// `Process::cmdline`. Processes with an empty or unreadable command line,
+/// such as kernel threads and the processes of other users under some
+/// `hidepid` settings, are skipped rather than failing the search.
+pub fn find_by_cmdline(pattern: &str) -> Result<Vec<Process>> {
+    let mut processes = Vec::new();
+
+    for process in try!(scan()) {
+        match process.cmdline() {
+            Ok(Some(ref cmdline)) if cmdline.contains(pattern) => processes.push(process),
+            _ => ()
+        }
+    }
+
+    return Ok(processes);
+}
+
+/// Read open files of every process whose link target passes `filter`
+fn scan_open_files<F: Fn(&Path) -> bool>(filter: F) -> Result<Vec<(PID, OpenFile)>> {
+    let mut files = Vec::new();
+
+    for process in try!(scan()) {
+        let links = match process.fd_links() {
+            Ok(links) => links,
+            Err(ref e) if e.kind() == ErrorKind::NotFound
+                || e.kind() == ErrorKind::PermissionDenied => continue,
+            Err(e) => return Err(e)
+        };
+
+        for (fd, path) in links.into_iter().filter(|&(_, ref path)| filter(path)) {
+            match OpenFile::new(process.pid, fd, path) {
+                Ok(file) => files.push((process.pid, file)),
+                Err(ref e) if e.kind() == ErrorKind::NotFound
+                    || e.kind() == ErrorKind::PermissionDenied => (),
+                Err(e) => return Err(e)
+            }
         }
     }
 
-    return processes;
+    return Ok(files);
+}
+
+/// Read every open file descriptor of every process, like a minimal `lsof`.
+///
+/// Processes and descriptors that go away during the scan are skipped, as
+/// are processes whose descriptors this process isn't permitted to read - so
+/// unless this is run as root, only processes of the same user are included.
+/// This can return a very large number of entries; use `open_files_under` to
+/// only collect the files of interest.
+pub fn all_open_files() -> Result<Vec<(PID, OpenFile)>> {
+    scan_open_files(|_| true)
+}
+
+/// Find every process with a file open at or below `prefix`, like `lsof +D`.
+///
+/// Paths are compared by component, so `/var/log` matches `/var/log/syslog`
+/// but not `/var/logs`, and neither is resolved through symlinks. Only the
+/// matching descriptors are read in full, so this is much cheaper than
+/// filtering the result of `all_open_files`.
+pub fn open_files_under(prefix: &Path) -> Result<Vec<(PID, OpenFile)>> {
+    scan_open_files(|path| path.starts_with(prefix))
+}
+
+/// Return a vector of all processes owned by the user with the given name
+///
+/// Returns an error with the kind `NotFound` if there is no such user.
+pub fn processes_of_username(name: &str) -> Result<Vec<Process>> {
+    processes_of_user(try!(uid_for_name(name)))
 }
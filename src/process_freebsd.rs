@@ -0,0 +1,306 @@
+//! Read process information on FreeBSD
+//!
+//! This is the FreeBSD version of the `process` module, and covers a small
+//! part of what the Linux version does: the PID, parent, owner, name, state,
+//! CPU time, memory sizes and start time of each process. They are read from
+//! the `kinfo_proc` records returned by the `kern.proc` sysctls, which any
+//! user can read for every process.
+//!
+//! `kinfo_proc` has grown over FreeBSD releases, so only the fields up to
+//! `ki_comm` are read, and each record is found by its `ki_structsize`.
+
+use std::io::ErrorKind;
+use std::mem;
+use std::ptr;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+use libc::{c_char,c_int,c_long,c_uint,c_void,size_t,time_t};
+use libc::consts::os::posix88::{ENOMEM,ESRCH};
+
+use ::{Error,Result,PID,UID,GID};
+
+const CTL_KERN: c_int = 1;
+const KERN_PROC: c_int = 14;
+
+/// `kern.proc.pid.<pid>`, the record for a single process
+const KERN_PROC_PID: c_int = 1;
+
+/// `kern.proc.proc`, one record for every process rather than every thread
+const KERN_PROC_PROC: c_int = 8;
+
+/// Length of `ki_groups`
+const KI_NGROUPS: usize = 16;
+
+#[repr(C)]
+#[derive(Clone,Copy)]
+struct timeval {
+    tv_sec: time_t,
+    tv_usec: c_long
+}
+
+/// The start of `struct kinfo_proc` from `<sys/user.h>`, up to `ki_comm`
+#[repr(C)]
+#[derive(Clone,Copy)]
+struct kinfo_proc {
+    ki_structsize: c_int,
+    ki_layout: c_int,
+    ki_args: *mut c_void,
+    ki_paddr: *mut c_void,
+    ki_addr: *mut c_void,
+    ki_tracep: *mut c_void,
+    ki_textvp: *mut c_void,
+    ki_fd: *mut c_void,
+    ki_vmspace: *mut c_void,
+    ki_wchan: *const c_void,
+    ki_pid: PID,
+    ki_ppid: PID,
+    ki_pgid: PID,
+    ki_tpgid: PID,
+    ki_sid: PID,
+    ki_tsid: PID,
+    ki_jobc: i16,
+    ki_spare_short1: i16,
+    ki_tdev_freebsd11: u32,
+    ki_siglist: [u32; 4],
+    ki_sigmask: [u32; 4],
+    ki_sigignore: [u32; 4],
+    ki_sigcatch: [u32; 4],
+    ki_uid: UID,
+    ki_ruid: UID,
+    ki_svuid: UID,
+    ki_rgid: GID,
+    ki_svgid: GID,
+    ki_ngroups: i16,
+    ki_spare_short2: i16,
+    ki_groups: [GID; KI_NGROUPS],
+    ki_size: usize,
+    ki_rssize: isize,
+    ki_swrss: isize,
+    ki_tsize: isize,
+    ki_dsize: isize,
+    ki_ssize: isize,
+    ki_xstat: u16,
+    ki_acflag: u16,
+    ki_pctcpu: u32,
+    ki_estcpu: u32,
+    ki_slptime: u32,
+    ki_swtime: u32,
+    ki_cow: u32,
+    ki_runtime: u64,
+    ki_start: timeval,
+    ki_childtime: timeval,
+    ki_flag: c_long,
+    ki_kiflag: c_long,
+    ki_traceflag: c_int,
+    ki_stat: c_char,
+    ki_nice: i8,
+    ki_lock: c_char,
+    ki_rqindex: c_char,
+    ki_oncpu_old: u8,
+    ki_lastcpu_old: u8,
+    ki_tdname: [c_char; 17],
+    ki_wmesg: [c_char; 9],
+    ki_login: [c_char; 18],
+    ki_lockname: [c_char; 9],
+    ki_comm: [c_char; 20]
+}
+
+extern "C" {
+    fn sysctl(name: *const c_int, namelen: c_uint, oldp: *mut c_void, oldlenp: *mut size_t,
+              newp: *const c_void, newlen: size_t) -> c_int;
+    fn getpagesize() -> c_int;
+}
+
+/// Read a `kern.proc` sysctl into a buffer, growing it until it fits
+///
+/// The list of processes can grow between asking for its size and reading
+/// it, in which case `sysctl` fails with `ENOMEM` and this tries again.
+fn read_sysctl(mib: &[c_int]) -> Result<Vec<u8>> {
+    loop {
+        let mut size: size_t = 0;
+        if unsafe { sysctl(mib.as_ptr(), mib.len() as c_uint, ptr::null_mut(), &mut size,
+                           ptr::null(), 0) } != 0 {
+            return Err(last_error());
+        }
+
+        let mut buffer: Vec<u8> = vec![0; size as usize + size as usize / 8];
+        size = buffer.len() as size_t;
+        if unsafe { sysctl(mib.as_ptr(), mib.len() as c_uint, buffer.as_mut_ptr() as *mut c_void,
+                           &mut size, ptr::null(), 0) } != 0 {
+            match Error::last_os_error().raw_os_error() {
+                Some(ENOMEM) => continue,
+                _ => return Err(last_error())
+            }
+        }
+
+        buffer.truncate(size as usize);
+        return Ok(buffer);
+    }
+}
+
+/// Return the error for the `errno` left by a failed `sysctl` call
+///
+/// `ESRCH` is returned as `NotFound`, as in the Linux module.
+fn last_error() -> Error {
+    let error = Error::last_os_error();
+    match error.raw_os_error() {
+        Some(ESRCH) => Error::new(ErrorKind::NotFound, "No such process"),
+        _ => error
+    }
+}
+
+/// Split the output of a `kern.proc` sysctl into its `kinfo_proc` records
+fn records(buffer: &[u8]) -> Result<Vec<kinfo_proc>> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+
+    while offset < buffer.len() {
+        let rest = &buffer[offset..];
+        if rest.len() < mem::size_of::<kinfo_proc>() {
+            return Err(Error::new(ErrorKind::Other, "Truncated kinfo_proc record"));
+        }
+
+        let record = unsafe { ptr::read_unaligned(rest.as_ptr() as *const kinfo_proc) };
+        if (record.ki_structsize as usize) < mem::size_of::<kinfo_proc>() {
+            return Err(Error::new(ErrorKind::Other,
+                format!("kinfo_proc record of {} bytes is too small", record.ki_structsize)));
+        }
+
+        offset += record.ki_structsize as usize;
+        records.push(record);
+    }
+
+    return Ok(records);
+}
+
+/// Convert a NUL terminated `c_char` array into a string
+fn from_c_chars(chars: &[c_char]) -> String {
+    let bytes: Vec<u8> = chars.iter().take_while(|&&c| c != 0).map(|&c| c as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// State of a process, from `ki_stat`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum State {
+    /// Being created by `fork` (`SIDL`)
+    Creating,
+    /// Running or runnable (`SRUN`)
+    Running,
+    /// Sleeping on an address (`SSLEEP`)
+    Sleeping,
+    /// Stopped by a signal or a debugger (`SSTOP`)
+    Stopped,
+    /// Exited and waiting to be collected by its parent (`SZOMB`)
+    Zombie,
+    /// Waiting for an interrupt (`SWAIT`)
+    Waiting,
+    /// Blocked on a lock (`SLOCK`)
+    Locked
+}
+
+impl State {
+    fn from_stat(stat: c_char) -> Result<State> {
+        match stat {
+            1 => Ok(State::Creating),
+            2 => Ok(State::Running),
+            3 => Ok(State::Sleeping),
+            4 => Ok(State::Stopped),
+            5 => Ok(State::Zombie),
+            6 => Ok(State::Waiting),
+            7 => Ok(State::Locked),
+            _ => Err(Error::new(ErrorKind::Other, format!("Invalid process state {}", stat)))
+        }
+    }
+}
+
+/// Information about a process, read from its `kinfo_proc`
+#[derive(Clone,Debug)]
+pub struct Process {
+    /// PID of the process
+    pub pid: PID,
+
+    /// PID of the parent process
+    pub ppid: PID,
+
+    /// Effective UID of the process
+    pub uid: UID,
+
+    /// Effective GID of the process
+    pub gid: GID,
+
+    /// Name of the process, up to 19 bytes long
+    pub comm: String,
+
+    /// State of the process
+    pub state: State,
+
+    /// Nice value (-20..20)
+    pub nice: i8,
+
+    /// Time scheduled in user and kernel mode together (seconds)
+    ///
+    /// FreeBSD only keeps the total in `kinfo_proc`.
+    pub cpu_time: f64,
+
+    /// Virtual memory size (bytes)
+    pub vsize: u64,
+
+    /// Resident set size (bytes)
+    pub rss: u64,
+
+    // Returned by `create_time`
+    start_time: SystemTime
+}
+
+impl Process {
+    /// Read information about a process from `kern.proc.pid.<pid>`.
+    ///
+    /// Returns a `NoSuchProcess` error if there is no such process.
+    pub fn new(pid: PID) -> Result<Process> {
+        let buffer = try!(read_sysctl(&[CTL_KERN, KERN_PROC, KERN_PROC_PID, pid])
+            .map_err(|e| Error::for_process(pid, e)));
+
+        match try!(records(&buffer)).first() {
+            Some(record) => Process::from_kinfo_proc(record),
+            None => Err(Error::NoSuchProcess { pid: pid })
+        }
+    }
+
+    fn from_kinfo_proc(record: &kinfo_proc) -> Result<Process> {
+        let page_size = unsafe { getpagesize() } as u64;
+
+        return Ok(Process {
+            pid: record.ki_pid,
+            ppid: record.ki_ppid,
+            uid: record.ki_uid,
+            gid: record.ki_groups[0],
+            comm: from_c_chars(&record.ki_comm),
+            state: try!(State::from_stat(record.ki_stat)),
+            nice: record.ki_nice,
+            cpu_time: record.ki_runtime as f64 / 1e6,
+            vsize: record.ki_size as u64,
+            rss: record.ki_rssize as u64 * page_size,
+            start_time: UNIX_EPOCH + Duration::new(record.ki_start.tv_sec as u64,
+                record.ki_start.tv_usec as u32 * 1000)
+        });
+    }
+
+    /// Return the time the process was started.
+    ///
+    /// This never fails on FreeBSD, and returns a `Result` to match Linux.
+    pub fn create_time(&self) -> Result<SystemTime> {
+        Ok(self.start_time)
+    }
+}
+
+/// Return a vector of all processes
+///
+/// Every process is read with a single `kern.proc.proc` sysctl. This panics
+/// if the sysctl fails.
+pub fn all() -> Vec<Process> {
+    let buffer = read_sysctl(&[CTL_KERN, KERN_PROC, KERN_PROC_PROC]).unwrap();
+
+    return records(&buffer).unwrap().iter()
+        .map(|record| Process::from_kinfo_proc(record).unwrap())
+        .collect();
+}
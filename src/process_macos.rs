@@ -0,0 +1,295 @@
+//! Read process information on macOS
+//!
+//! This is the macOS version of the `process` module, and covers a small part
+//! of what the Linux version does: the PID, parent, owner, name, state, CPU
+//! times, memory sizes and start time of each process. They are read with
+//! `proc_pidinfo(PROC_PIDTASKALLINFO)` from `libproc`, which returns them all
+//! in a single call.
+//!
+//! ### Permissions
+//!
+//! Unless run as root, `proc_pidinfo` only describes processes owned by the
+//! calling user, and returns a `PermissionDenied` error for any other.
+//! `all` skips those processes rather than failing.
+
+use std::io::ErrorKind;
+use std::mem;
+use std::ptr;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+use libc::{c_char,c_int,c_void};
+use libc::consts::os::posix88::ESRCH;
+
+use ::{Error,Result,PID,UID,GID};
+
+/// `proc_listpids` type listing every process
+const PROC_ALL_PIDS: u32 = 1;
+
+/// `proc_pidinfo` flavor returning a `proc_taskallinfo`
+const PROC_PIDTASKALLINFO: c_int = 2;
+
+/// Length of `pbi_comm`, which `pbi_name` is twice as long as
+const MAXCOMLEN: usize = 16;
+
+#[repr(C)]
+struct proc_bsdinfo {
+    pbi_flags: u32,
+    pbi_status: u32,
+    pbi_xstatus: u32,
+    pbi_pid: u32,
+    pbi_ppid: u32,
+    pbi_uid: UID,
+    pbi_gid: GID,
+    pbi_ruid: UID,
+    pbi_rgid: GID,
+    pbi_svuid: UID,
+    pbi_svgid: GID,
+    rfu_1: u32,
+    pbi_comm: [c_char; MAXCOMLEN],
+    pbi_name: [c_char; 2 * MAXCOMLEN],
+    pbi_nfiles: u32,
+    pbi_pgid: u32,
+    pbi_pjobc: u32,
+    e_tdev: u32,
+    e_tpgid: u32,
+    pbi_nice: i32,
+    pbi_start_tvsec: u64,
+    pbi_start_tvusec: u64
+}
+
+#[repr(C)]
+struct proc_taskinfo {
+    pti_virtual_size: u64,
+    pti_resident_size: u64,
+    pti_total_user: u64,
+    pti_total_system: u64,
+    pti_threads_user: u64,
+    pti_threads_system: u64,
+    pti_policy: i32,
+    pti_faults: i32,
+    pti_pageins: i32,
+    pti_cow_faults: i32,
+    pti_messages_sent: i32,
+    pti_messages_received: i32,
+    pti_syscalls_mach: i32,
+    pti_syscalls_unix: i32,
+    pti_csw: i32,
+    pti_threadnum: i32,
+    pti_numrunning: i32,
+    pti_priority: i32
+}
+
+#[repr(C)]
+struct proc_taskallinfo {
+    pbsd: proc_bsdinfo,
+    ptinfo: proc_taskinfo
+}
+
+#[repr(C)]
+struct mach_timebase_info {
+    numer: u32,
+    denom: u32
+}
+
+extern "C" {
+    fn proc_listpids(kind: u32, typeinfo: u32, buffer: *mut c_void, buffersize: c_int) -> c_int;
+    fn proc_pidinfo(pid: c_int, flavor: c_int, arg: u64, buffer: *mut c_void,
+                    buffersize: c_int) -> c_int;
+    fn mach_timebase_info(info: *mut mach_timebase_info) -> c_int;
+}
+
+/// Return the error for the `errno` left by a failed `libproc` call
+///
+/// `ESRCH` is returned as `NotFound`, as in the Linux module, so that scans
+/// can skip processes that exit while they are being read.
+/// `Error::for_process` turns it into `NoSuchProcess` where the PID is known.
+fn last_error() -> Error {
+    let error = Error::last_os_error();
+    match error.raw_os_error() {
+        Some(ESRCH) => Error::new(ErrorKind::NotFound, "No such process"),
+        _ => error
+    }
+}
+
+/// Convert a NUL terminated `c_char` array into a string
+fn from_c_chars(chars: &[c_char]) -> String {
+    let bytes: Vec<u8> = chars.iter().take_while(|&&c| c != 0).map(|&c| c as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Convert a CPU time from Mach absolute time units to seconds
+///
+/// These are nanoseconds on Intel Macs, but not on Apple silicon, so they are
+/// scaled by the ratio from `mach_timebase_info`.
+fn mach_to_seconds(time: u64) -> f64 {
+    let mut timebase = mach_timebase_info { numer: 1, denom: 1 };
+    unsafe { mach_timebase_info(&mut timebase) };
+
+    let nanoseconds = time as f64 * timebase.numer as f64 / timebase.denom.max(1) as f64;
+    return nanoseconds / 1e9;
+}
+
+/// State of a process, from `pbi_status`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum State {
+    /// Being created by `fork` (`SIDL`)
+    Creating,
+    /// Running or runnable (`SRUN`)
+    Running,
+    /// Sleeping on an address (`SSLEEP`)
+    Sleeping,
+    /// Stopped by a signal or a debugger (`SSTOP`)
+    Stopped,
+    /// Exited and waiting to be collected by its parent (`SZOMB`)
+    Zombie
+}
+
+impl State {
+    fn from_status(status: u32) -> Result<State> {
+        match status {
+            1 => Ok(State::Creating),
+            2 => Ok(State::Running),
+            3 => Ok(State::Sleeping),
+            4 => Ok(State::Stopped),
+            5 => Ok(State::Zombie),
+            _ => Err(Error::new(ErrorKind::Other, format!("Invalid process status {}", status)))
+        }
+    }
+}
+
+/// Information about a process, read with `proc_pidinfo`
+#[derive(Clone,Debug)]
+pub struct Process {
+    /// PID of the process
+    pub pid: PID,
+
+    /// PID of the parent process
+    pub ppid: PID,
+
+    /// Effective UID of the process
+    pub uid: UID,
+
+    /// Effective GID of the process
+    pub gid: GID,
+
+    /// Name of the process, up to 32 bytes long
+    pub comm: String,
+
+    /// State of the process
+    pub state: State,
+
+    /// Nice value (-20..19)
+    pub nice: i32,
+
+    /// Time scheduled in user mode (seconds)
+    pub utime: f64,
+
+    /// Time scheduled in kernel mode (seconds)
+    pub stime: f64,
+
+    /// Number of threads in the process
+    pub num_threads: i32,
+
+    /// Virtual memory size (bytes)
+    pub vsize: u64,
+
+    /// Resident set size (bytes)
+    pub rss: u64,
+
+    // Returned by `create_time`
+    start_time: SystemTime
+}
+
+impl Process {
+    /// Read information about a process with `proc_pidinfo`.
+    ///
+    /// Returns a `NoSuchProcess` error if there is no such process, and
+    /// `AccessDenied` for a process owned by another user unless called as
+    /// root.
+    pub fn new(pid: PID) -> Result<Process> {
+        let mut info: proc_taskallinfo = unsafe { mem::zeroed() };
+        let size = mem::size_of::<proc_taskallinfo>() as c_int;
+
+        let read = unsafe {
+            proc_pidinfo(pid, PROC_PIDTASKALLINFO, 0, &mut info as *mut _ as *mut c_void, size)
+        };
+        if read <= 0 {
+            return Err(Error::for_process(pid, last_error()));
+        }
+        if read < size {
+            return Err(Error::new(ErrorKind::Other,
+                "proc_pidinfo returned less than a proc_taskallinfo"));
+        }
+
+        let name = match from_c_chars(&info.pbsd.pbi_name) {
+            ref name if name.is_empty() => from_c_chars(&info.pbsd.pbi_comm),
+            name => name
+        };
+
+        return Ok(Process {
+            pid: info.pbsd.pbi_pid as PID,
+            ppid: info.pbsd.pbi_ppid as PID,
+            uid: info.pbsd.pbi_uid,
+            gid: info.pbsd.pbi_gid,
+            comm: name,
+            state: try!(State::from_status(info.pbsd.pbi_status)),
+            nice: info.pbsd.pbi_nice,
+            utime: mach_to_seconds(info.ptinfo.pti_total_user),
+            stime: mach_to_seconds(info.ptinfo.pti_total_system),
+            num_threads: info.ptinfo.pti_threadnum,
+            vsize: info.ptinfo.pti_virtual_size,
+            rss: info.ptinfo.pti_resident_size,
+            start_time: UNIX_EPOCH + Duration::new(info.pbsd.pbi_start_tvsec,
+                info.pbsd.pbi_start_tvusec as u32 * 1000)
+        });
+    }
+
+    /// Return the time the process was started.
+    ///
+    /// This never fails on macOS, and returns a `Result` to match Linux.
+    pub fn create_time(&self) -> Result<SystemTime> {
+        Ok(self.start_time)
+    }
+}
+
+/// Return the PID of every process
+///
+/// The list comes from `proc_listpids`, and includes PID 0 (`kernel_task`).
+pub fn pids() -> Result<Vec<PID>> {
+    let needed = unsafe { proc_listpids(PROC_ALL_PIDS, 0, ptr::null_mut(), 0) };
+    if needed <= 0 {
+        return Err(last_error());
+    }
+
+    // Leave room for processes started between the two calls
+    let mut pids: Vec<PID> = vec![0; needed as usize / mem::size_of::<PID>() + 64];
+    let size = (pids.len() * mem::size_of::<PID>()) as c_int;
+
+    let read = unsafe { proc_listpids(PROC_ALL_PIDS, 0, pids.as_mut_ptr() as *mut c_void, size) };
+    if read <= 0 {
+        return Err(last_error());
+    }
+
+    pids.truncate(read as usize / mem::size_of::<PID>());
+    return Ok(pids);
+}
+
+/// Return a vector of all processes
+///
+/// Processes that exit during the scan, and those `proc_pidinfo` won't
+/// describe to the calling user, are skipped. This panics if the list of
+/// processes can't be read.
+pub fn all() -> Vec<Process> {
+    let mut processes = Vec::new();
+
+    for pid in pids().unwrap() {
+        match Process::new(pid) {
+            Ok(process) => processes.push(process),
+            Err(ref e) if e.kind() == ErrorKind::NotFound
+                || e.kind() == ErrorKind::PermissionDenied => (),
+            Err(e) => panic!("Could not read process {}: {}", pid, e)
+        }
+    }
+
+    return processes;
+}
@@ -1,9 +1,20 @@
 //! Read information about the operating system from `/proc`
 
+use std::fs::OpenOptions;
+use std::io::{ErrorKind,Write};
 use std::str::FromStr;
-use std::path::Path;
+use std::path::{Path,PathBuf};
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
 
-use ::utils::read_file;
+use libc::c_int;
+use libc::consts::os::sysconf::_SC_NPROCESSORS_ONLN;
+use libc::funcs::posix88::unistd::sysconf;
+
+use ::{Error,Result};
+use ::utils::{read_file,ticks_per_second};
+
+/// `errno` for an operation that isn't supported, which libc 0.1 doesn't have
+const EOPNOTSUPP: c_int = 95;
 
 /// Returns the system uptime in seconds
 ///
@@ -14,3 +25,258 @@ pub fn uptime() -> isize {
     let uptime: Vec<&str> = numbers[0].split('.').collect();
     return FromStr::from_str(uptime[0]).unwrap();
 }
+
+/// Returns the time the system was booted
+///
+/// Read from the `btime` line of `/proc/stat`, which has a resolution of one
+/// second.
+pub fn boot_time() -> Result<SystemTime> {
+    let data = try!(read_file(&Path::new("/proc/stat")));
+
+    for line in data.lines() {
+        let mut fields = line.split_whitespace();
+        if fields.next() == Some("btime") {
+            let btime: u64 = try!(fields.next().and_then(|f| f.parse().ok()).ok_or(
+                Error::new(ErrorKind::Other, "Could not parse btime in /proc/stat")));
+            return Ok(UNIX_EPOCH + Duration::from_secs(btime));
+        }
+    }
+
+    return Err(Error::new(ErrorKind::Other, "No btime line in /proc/stat"));
+}
+
+/// Time spent by the CPUs in each mode since boot, summed across all CPUs
+///
+/// Read from the first line of `/proc/stat`. All values are in seconds, and
+/// fields not reported by older kernels are zero.
+#[derive(Clone,Copy,Debug)]
+pub struct CpuTimes {
+    /// Time spent in user mode, including `guest`
+    pub user: f64,
+
+    /// Time spent in user mode with a positive nice value, including `guest_nice`
+    pub nice: f64,
+
+    /// Time spent in kernel mode
+    pub system: f64,
+
+    /// Time spent idle
+    pub idle: f64,
+
+    /// Time spent idle while waiting for I/O
+    pub iowait: f64,
+
+    /// Time spent servicing interrupts
+    pub irq: f64,
+
+    /// Time spent servicing softirqs
+    pub softirq: f64,
+
+    /// Time stolen by the hypervisor for other virtual machines
+    pub steal: f64,
+
+    /// Time spent running a virtual CPU for guest operating systems
+    pub guest: f64,
+
+    /// Time spent running a niced guest
+    pub guest_nice: f64
+}
+
+impl CpuTimes {
+    /// Time spent doing anything other than idling
+    pub fn busy(&self) -> f64 {
+        self.user + self.nice + self.system + self.irq + self.softirq + self.steal
+    }
+
+    /// Time spent in every mode
+    pub fn total(&self) -> f64 {
+        self.busy() + self.idle + self.iowait
+    }
+}
+
+/// Returns the time spent by the CPUs in each mode since boot
+pub fn cpu_times() -> Result<CpuTimes> {
+    let data = try!(read_file(&Path::new("/proc/stat")));
+    let line = try!(data.lines().find(|l| l.starts_with("cpu ")).ok_or(
+        Error::new(ErrorKind::Other, "No cpu line in /proc/stat")));
+
+    let ticks_per_second = ticks_per_second() as f64;
+    let mut ticks = Vec::new();
+    for field in line.split_whitespace().skip(1) {
+        let value: u64 = try!(field.parse().map_err(|_| {
+            Error::new(ErrorKind::Other, "Could not parse cpu line in /proc/stat")
+        }));
+        ticks.push(value as f64 / ticks_per_second);
+    }
+    ticks.resize(10, 0.0);
+
+    return Ok(CpuTimes {
+        user:       ticks[0],
+        nice:       ticks[1],
+        system:     ticks[2],
+        idle:       ticks[3],
+        iowait:     ticks[4],
+        irq:        ticks[5],
+        softirq:    ticks[6],
+        steal:      ticks[7],
+        guest:      ticks[8],
+        guest_nice: ticks[9]
+    });
+}
+
+/// Returns the number of logical CPUs that are currently online
+///
+/// Each hyperthread counts as a CPU. This is never less than one.
+pub fn cpu_count() -> u64 {
+    match unsafe { sysconf(_SC_NPROCESSORS_ONLN) } {
+        n if n > 0 => n as u64,
+        _ => 1
+    }
+}
+
+/// Translate a sysctl key to its path under `/proc/sys`
+///
+/// Keys are separated by dots as in `kernel.pid_max`, or by slashes as in
+/// `net/ipv4/conf/eth0.1/rp_filter` when a component itself contains a dot.
+fn sysctl_path(key: &str) -> Result<PathBuf> {
+    let separator = if key.contains('/') { '/' } else { '.' };
+    let mut path = PathBuf::from("/proc/sys");
+
+    for component in key.split(separator) {
+        if component.is_empty() || component == "." || component == ".." {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                format!("Invalid sysctl key: {}", key)));
+        }
+        path.push(component);
+    }
+
+    return Ok(path);
+}
+
+/// Returns the value of a kernel parameter from `/proc/sys`
+///
+/// `key` is in the dotted form used by `sysctl(8)`, e.g. `kernel.pid_max`,
+/// and the trailing newline is removed from the value. Keys that would
+/// escape `/proc/sys` return an `InvalidInput` error.
+pub fn read_sysctl(key: &str) -> Result<String> {
+    let value = try!(read_file(&try!(sysctl_path(key))));
+    Ok(value.trim_right_matches('\n').to_string())
+}
+
+/// Sets the value of a kernel parameter in `/proc/sys`
+///
+/// `key` is checked as for `read_sysctl`. Most parameters can only be written
+/// by root, and return a `PermissionDenied` error otherwise.
+pub fn write_sysctl(key: &str, value: &str) -> Result<()> {
+    let mut file = try!(OpenOptions::new().write(true).open(try!(sysctl_path(key))));
+    file.write_all(value.as_bytes()).map_err(Error::from)
+}
+
+/// One line of a `/proc/pressure` file
+///
+/// The averages are the percentage of time that tasks were stalled over the
+/// last 10, 60 and 300 seconds.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct PressureLine {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+
+    /// Total time tasks have been stalled since boot
+    pub total: Duration
+}
+
+/// Pressure stall information for a single resource
+///
+/// Read from `/proc/pressure/cpu`, `/proc/pressure/memory` or
+/// `/proc/pressure/io`.
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct PressureResource {
+    /// Time at least some tasks were stalled waiting for the resource
+    pub some: PressureLine,
+
+    /// Time all non-idle tasks were stalled at once, which is missing from
+    /// `cpu` before Linux 5.13
+    pub full: Option<PressureLine>
+}
+
+impl FromStr for PressureResource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::new(ErrorKind::Other, "Could not parse /proc/pressure");
+        let mut some = None;
+        let mut full = None;
+
+        for line in s.lines() {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next();
+            let mut pressure = PressureLine::default();
+
+            for field in fields {
+                let mut parts = field.splitn(2, '=');
+                let (key, value) = match (parts.next(), parts.next()) {
+                    (Some(key), Some(value)) => (key, value),
+                    _ => return Err(invalid())
+                };
+
+                match key {
+                    "avg10" => pressure.avg10 = try!(value.parse().map_err(|_| invalid())),
+                    "avg60" => pressure.avg60 = try!(value.parse().map_err(|_| invalid())),
+                    "avg300" => pressure.avg300 = try!(value.parse().map_err(|_| invalid())),
+                    "total" => pressure.total = Duration::from_micros(
+                        try!(value.parse().map_err(|_| invalid()))),
+                    _ => ()
+                }
+            }
+
+            match kind {
+                Some("some") => some = Some(pressure),
+                Some("full") => full = Some(pressure),
+                _ => ()
+            }
+        }
+
+        return Ok(PressureResource {
+            some: try!(some.ok_or_else(invalid)),
+            full: full
+        });
+    }
+}
+
+/// Pressure stall information for the CPU, memory and I/O
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct Pressure {
+    pub cpu: PressureResource,
+    pub memory: PressureResource,
+    pub io: PressureResource
+}
+
+/// Read a file from `/proc/pressure`
+fn read_pressure(resource: &str) -> Result<PressureResource> {
+    let path = Path::new("/proc/pressure").join(resource);
+
+    match read_file(&path) {
+        Ok(contents) => FromStr::from_str(&contents),
+        // The files are missing without CONFIG_PSI, and can't be read when
+        // the kernel was booted with psi=0
+        Err(ref e) if e.kind() == ErrorKind::NotFound || e.raw_os_error() == Some(EOPNOTSUPP) => {
+            Err(Error::new(ErrorKind::Other,
+                "Pressure stall information is not supported by this kernel"))
+        },
+        Err(e) => Err(e)
+    }
+}
+
+/// Returns pressure stall information for the whole system
+///
+/// Pressure stall information (PSI) measures how long tasks wait for the CPU,
+/// memory and I/O, and is available on Linux 4.20 and later. Kernels without
+/// it, or with it disabled, return an error with the kind `Other`.
+pub fn pressure() -> Result<Pressure> {
+    return Ok(Pressure {
+        cpu: try!(read_pressure("cpu")),
+        memory: try!(read_pressure("memory")),
+        io: try!(read_pressure("io"))
+    });
+}
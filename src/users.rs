@@ -0,0 +1,139 @@
+//! Look up users and groups in the system password and group databases
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr,CString};
+use std::io::ErrorKind;
+use std::ptr;
+
+use libc::{c_char,c_int,size_t};
+use libc::consts::os::posix88::ERANGE;
+
+use ::{Error,Result,UID,GID};
+
+#[repr(C)]
+struct passwd {
+    pw_name: *mut c_char,
+    pw_passwd: *mut c_char,
+    pw_uid: UID,
+    pw_gid: GID,
+    pw_gecos: *mut c_char,
+    pw_dir: *mut c_char,
+    pw_shell: *mut c_char
+}
+
+#[repr(C)]
+struct group {
+    gr_name: *mut c_char,
+    gr_passwd: *mut c_char,
+    gr_gid: GID,
+    gr_mem: *mut *mut c_char
+}
+
+extern "C" {
+    fn getpwnam_r(name: *const c_char, pwd: *mut passwd, buf: *mut c_char,
+                  buflen: size_t, result: *mut *mut passwd) -> c_int;
+    fn getpwuid_r(uid: UID, pwd: *mut passwd, buf: *mut c_char,
+                  buflen: size_t, result: *mut *mut passwd) -> c_int;
+    fn getgrgid_r(gid: GID, grp: *mut group, buf: *mut c_char,
+                  buflen: size_t, result: *mut *mut group) -> c_int;
+}
+
+thread_local! {
+    // Names are looked up once per thread, as listing processes asks for the
+    // same few owners over and over
+    static USER_NAMES: RefCell<HashMap<UID, String>> = RefCell::new(HashMap::new());
+    static GROUP_NAMES: RefCell<HashMap<GID, String>> = RefCell::new(HashMap::new());
+}
+
+/// Call a `get*_r` function, growing the buffer until the entry fits
+///
+/// Returns the name of the entry, or `None` if there is no such entry.
+fn lookup<T, F>(get: F, name: fn(&T) -> *mut c_char) -> Result<Option<String>>
+    where F: Fn(*mut T, *mut c_char, size_t, *mut *mut T) -> c_int {
+    let mut buffer: Vec<c_char> = Vec::with_capacity(1024);
+    loop {
+        let mut entry: T = unsafe { ::std::mem::zeroed() };
+        let mut result: *mut T = ptr::null_mut();
+
+        match get(&mut entry, buffer.as_mut_ptr(), buffer.capacity() as size_t, &mut result) {
+            0 if result.is_null() => return Ok(None),
+            0 => {
+                let name = unsafe { CStr::from_ptr(name(&entry)) };
+                return Ok(Some(name.to_string_lossy().into_owned()));
+            },
+            ERANGE => {
+                let capacity = buffer.capacity() * 2;
+                buffer.reserve(capacity);
+            },
+            code => return Err(Error::from_raw_os_error(code))
+        }
+    }
+}
+
+/// Return the name of the user with the given UID
+///
+/// If there is no such user the UID is returned as a string, as `ps` does.
+/// Results are cached for the life of the calling thread.
+pub fn name_for_uid(uid: UID) -> Result<String> {
+    if let Some(name) = USER_NAMES.with(|names| names.borrow().get(&uid).cloned()) {
+        return Ok(name);
+    }
+
+    let name = try!(lookup(|pwd, buf, len, result| unsafe {
+        getpwuid_r(uid, pwd, buf, len, result)
+    }, |pwd: &passwd| pwd.pw_name)).unwrap_or_else(|| uid.to_string());
+
+    USER_NAMES.with(|names| names.borrow_mut().insert(uid, name.clone()));
+    return Ok(name);
+}
+
+/// Return the name of the group with the given GID
+///
+/// If there is no such group the GID is returned as a string. Results are
+/// cached for the life of the calling thread.
+pub fn name_for_gid(gid: GID) -> Result<String> {
+    if let Some(name) = GROUP_NAMES.with(|names| names.borrow().get(&gid).cloned()) {
+        return Ok(name);
+    }
+
+    let name = try!(lookup(|grp, buf, len, result| unsafe {
+        getgrgid_r(gid, grp, buf, len, result)
+    }, |grp: &group| grp.gr_name)).unwrap_or_else(|| gid.to_string());
+
+    GROUP_NAMES.with(|names| names.borrow_mut().insert(gid, name.clone()));
+    return Ok(name);
+}
+
+/// Return the UID of the user with the given name
+///
+/// Returns an error with the kind `NotFound` if there is no such user.
+pub fn uid_for_name(name: &str) -> Result<UID> {
+    let cname = try!(CString::new(name).map_err(|_| {
+        Error::new(ErrorKind::InvalidInput, "User name contains a NUL byte")
+    }));
+
+    let mut buffer: Vec<c_char> = Vec::with_capacity(1024);
+    loop {
+        let mut pwd: passwd = unsafe { ::std::mem::zeroed() };
+        let mut result: *mut passwd = ptr::null_mut();
+
+        let code = unsafe {
+            getpwnam_r(cname.as_ptr(), &mut pwd, buffer.as_mut_ptr(),
+                       buffer.capacity() as size_t, &mut result)
+        };
+
+        match code {
+            0 if result.is_null() => {
+                return Err(Error::new(ErrorKind::NotFound,
+                    format!("No such user: {}", name)));
+            },
+            0 => return Ok(pwd.pw_uid),
+            ERANGE => {
+                let capacity = buffer.capacity() * 2;
+                buffer.reserve(capacity);
+            },
+            code => return Err(Error::from_raw_os_error(code))
+        }
+    }
+}
@@ -1,12 +1,221 @@
 //! Utility methods, mostly for dealing with IO
 
+use std::cell::RefCell;
 use std::fs::File;
-use std::io::{Read,Result};
+use std::io::{ErrorKind,Read};
 use std::path::Path;
+use std::str;
+use std::sync::atomic::{AtomicBool,AtomicUsize,Ordering};
+use std::thread;
+use std::time::Duration;
 
+use libc::consts::os::sysconf::{_SC_CLK_TCK,_SC_PAGESIZE};
+use libc::funcs::posix88::unistd::sysconf;
+
+use ::{Error,Result};
+
+/// Delay before the first retry, which doubles for each one after it
+const READ_RETRY_DELAY_MS: u64 = 1;
+
+/// How reads of files in `/proc` are retried after transient errors
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct ReadPolicy {
+    /// Number of times a read is retried after the first attempt
+    pub max_retries: u32,
+
+    /// Also retry `NotFound` errors
+    ///
+    /// In `/proc/[pid]` these usually mean the process has exited, so
+    /// retrying only delays the error, but a file can also briefly be missing
+    /// while the process is being created or is exiting.
+    pub retry_not_found: bool
+}
+
+impl Default for ReadPolicy {
+    fn default() -> ReadPolicy {
+        ReadPolicy { max_retries: 2, retry_not_found: false }
+    }
+}
+
+// The policy used by `read_file`, as set by `set_read_retries`
+static READ_RETRIES: AtomicUsize = AtomicUsize::new(2);
+static RETRY_NOT_FOUND: AtomicBool = AtomicBool::new(false);
+
+/// Set how every read of a file in `/proc` is retried after a transient error
+///
+/// This applies to all threads. The default is two retries, and not to retry
+/// `NotFound` errors.
+pub fn set_read_retries(max_retries: u32, retry_not_found: bool) {
+    READ_RETRIES.store(max_retries as usize, Ordering::Relaxed);
+    RETRY_NOT_FOUND.store(retry_not_found, Ordering::Relaxed);
+}
+
+/// Return the policy set by `set_read_retries`
+pub fn read_policy() -> ReadPolicy {
+    ReadPolicy {
+        max_retries: READ_RETRIES.load(Ordering::Relaxed) as u32,
+        retry_not_found: RETRY_NOT_FOUND.load(Ordering::Relaxed)
+    }
+}
+
+/// Run `read`, retrying it after a short delay if it fails transiently
+///
+/// Errors that say nothing about the file itself are retried, such as a
+/// signal interrupting `open(2)`, and so are `UnexpectedEof` errors, which
+/// reads of `/proc` return when a file fails after part of it was read.
+/// `NotFound` errors are only retried if the policy says so. Up to `max_retries` retries
+/// are made, and the error from the last attempt is returned.
+pub fn retry<T, F: FnMut() -> Result<T>>(policy: ReadPolicy, mut read: F) -> Result<T> {
+    let mut delay = Duration::from_millis(READ_RETRY_DELAY_MS);
+
+    for _ in 0..policy.max_retries {
+        match read() {
+            Err(ref e) if e.kind() == ErrorKind::Interrupted
+                || e.kind() == ErrorKind::WouldBlock
+                || e.kind() == ErrorKind::UnexpectedEof
+                || (e.kind() == ErrorKind::NotFound && policy.retry_not_found) => {
+                thread::sleep(delay);
+                delay *= 2;
+            },
+            result => return result
+        }
+    }
+
+    return read();
+}
+
+/// Size of the buffer files are first read into
+const READ_BUFFER_SIZE: usize = 4096;
+
+thread_local! {
+    // Reused between reads, so it only has to grow to fit a large file once
+    static READ_BUFFER: RefCell<Vec<u8>> = RefCell::new(vec![0; READ_BUFFER_SIZE]);
+}
+
+/// Read a whole file into `buffer`, in a single `read(2)` call if it fits
+///
+/// Most files in `/proc` are generated as they are read, so reading one in
+/// several calls can mix the state of the system at different times - such as
+/// a `stat` file whose fields change between two reads. The file is read into
+/// the buffer, and if that fills it the buffer is doubled and the file is
+/// opened and read again, until the whole file fits in a single read. Files
+/// that return less than was asked for before reaching their end are
+/// completed with further reads, and so are not a single snapshot: files
+/// listing many records, like `maps`, `smaps` and `mountinfo`, return at most
+/// a page per read, so only small ones are read in one call.
+///
+/// The buffer is never shrunk, so reusing it for many files only allocates
+/// until it fits the largest. Returns the open file, which can be used to
+/// read its metadata, and the length of the contents at the start of the
+/// buffer. If a read fails after the first has returned part of the file,
+/// this returns an `UnexpectedEof` error describing it, which `retry` retries.
+pub fn read_whole_into(path: &Path, buffer: &mut Vec<u8>) -> Result<(File, usize)> {
+    if buffer.len() < READ_BUFFER_SIZE {
+        buffer.resize(READ_BUFFER_SIZE, 0);
+    }
+
+    loop {
+        let mut file = try!(File::open(path));
+        let size = buffer.len();
+        let read = try!(file.read(&mut buffer[..]));
+
+        if read < size {
+            if read == 0 {
+                return Ok((file, 0));
+            }
+
+            buffer.truncate(read);
+            if let Err(e) = file.read_to_end(buffer) {
+                return Err(Error::new(ErrorKind::UnexpectedEof, format!(
+                    "Reading {} failed after {} bytes: {}", path.display(), read, e)));
+            }
+            let len = buffer.len();
+            if len < size {
+                buffer.resize(size, 0);
+            }
+            return Ok((file, len));
+        }
+
+        buffer.resize(size * 2, 0);
+    }
+}
+
+/// Run `f` with the buffer shared by the calling thread
+///
+/// If the buffer is already in use, because `f` was called from within
+/// another file being parsed, a new buffer is used instead.
+fn with_buffer<T, F: FnOnce(&mut Vec<u8>) -> T>(f: F) -> T {
+    READ_BUFFER.with(|shared| match shared.try_borrow_mut() {
+        Ok(mut buffer) => f(&mut buffer),
+        Err(_) => f(&mut Vec::new())
+    })
+}
+
+/// Read a whole file and pass its contents to `parse`, without copying them
+///
+/// The file is read into the buffer shared by the calling thread, as for
+/// `read_whole_into`, and transient errors are retried as for `read_file`.
+/// This saves allocating for the contents when they are only parsed.
+pub fn with_file_bytes<T, F: FnOnce(&[u8]) -> Result<T>>(path: &Path, parse: F) -> Result<T> {
+    with_buffer(|buffer| {
+        let (_, len) = try!(retry(read_policy(), || read_whole_into(path, buffer)));
+        parse(&buffer[..len])
+    })
+}
+
+/// Read a whole file and pass its contents to `parse` as a string
+///
+/// See `with_file_bytes`. Returns an `InvalidData` error if the file isn't
+/// valid UTF-8.
+pub fn with_file<T, F: FnOnce(&str) -> Result<T>>(path: &Path, parse: F) -> Result<T> {
+    with_file_bytes(path, |bytes| {
+        let contents = try!(str::from_utf8(bytes).map_err(|_| {
+            Error::new(ErrorKind::InvalidData, format!("{} is not valid UTF-8", path.display()))
+        }));
+        parse(contents)
+    })
+}
+
+/// Read a whole file as a string
+///
+/// See `read_whole_into`, which reads most small files in `/proc` in a
+/// single call, and `with_file` to parse a file without copying it into a
+/// string. Returns an `InvalidData` error if the file isn't valid UTF-8.
 pub fn read_file(path: &Path) -> Result<String> {
-    let mut buffer = String::new();
-    let mut file = try!(File::open(path));
-    try!(file.read_to_string(&mut buffer));
-    return Ok(buffer);
+    with_file(path, |contents| Ok(contents.to_owned()))
+}
+
+/// Read a whole file as bytes, see `read_file`
+///
+/// Transient errors are retried as set by `set_read_retries`, including a
+/// file that fails part way through being read, such as `/proc/[pid]/stack`
+/// when the process exits between two reads of it.
+pub fn read_file_bytes(path: &Path) -> Result<Vec<u8>> {
+    with_file_bytes(path, |contents| Ok(contents.to_vec()))
+}
+
+/// Return the number of clock ticks per second
+///
+/// This is 'safe' to call as sysconf should only return an error for invalid
+/// inputs, or options and limits (which _SC_CLK_TCK is not).
+pub fn ticks_per_second() -> u64 {
+    unsafe { sysconf(_SC_CLK_TCK) as u64 }
+}
+
+/// Return the size of a page in bytes
+pub fn page_size() -> u64 {
+    unsafe { sysconf(_SC_PAGESIZE) as u64 }
+}
+
+/// Convert a number of clock ticks to a `Duration`
+///
+/// `ticks_per_second` is usually from `ticks_per_second()`, or the value in
+/// `CpuTimes` for a process parsed with a different one. Returns zero if it
+/// is zero.
+pub fn ticks_to_duration(ticks: u64, ticks_per_second: u64) -> Duration {
+    if ticks_per_second == 0 {
+        return Duration::new(0, 0);
+    }
+    let nanos = (ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second;
+    Duration::new(ticks / ticks_per_second, nanos as u32)
 }
@@ -0,0 +1,28 @@
+#![cfg(target_os = "linux")]
+
+extern crate psutil;
+
+use psutil::cpu::{frequency,parse_cpuinfo_mhz};
+
+#[test]
+fn cpu_frequency() {
+    for cpu in frequency().unwrap() {
+        assert!(cpu.current > 0.0);
+        assert!(cpu.min.unwrap_or(0.0) <= cpu.max.unwrap_or(cpu.current));
+    }
+}
+
+#[test]
+fn parse_cpuinfo() {
+    let frequencies = parse_cpuinfo_mhz(concat!(
+        "processor\t: 0\n",
+        "model name\t: Example CPU\n",
+        "cpu MHz\t\t: 2000.000\n",
+        "\n",
+        "processor\t: 1\n",
+        "cpu MHz\t\t: 1499.5\n")).unwrap();
+
+    assert_eq!(frequencies.len(), 2);
+    assert_eq!((frequencies[1].cpu, frequencies[1].current), (1, 1499.5));
+    assert_eq!(frequencies[0].max, None);
+}
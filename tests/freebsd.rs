@@ -0,0 +1,29 @@
+#![cfg(target_os = "freebsd")]
+
+extern crate psutil;
+
+use std::io::ErrorKind;
+
+use psutil::process::{Process,State,all};
+
+#[test]
+fn process_self() {
+    let process = Process::new(psutil::getpid()).unwrap();
+    assert_eq!(process.pid, psutil::getpid());
+    assert_eq!(process.ppid, psutil::getppid());
+    assert_eq!(process.state, State::Running);
+    assert!(process.rss > 0 && process.vsize >= process.rss);
+    assert!(!process.comm.is_empty());
+}
+
+#[test]
+fn process_missing() {
+    assert_eq!(Process::new(-1).unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_all() {
+    let processes = all();
+    assert!(processes.iter().any(|p| p.pid == psutil::getpid()));
+    assert!(processes.iter().any(|p| p.pid == 1 && p.ppid == 0));
+}
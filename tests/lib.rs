@@ -1,5 +1,7 @@
 extern crate psutil;
 
+use std::io;
+
 #[test]
 fn getpid() {
     assert!(psutil::getpid() != 0)
@@ -9,3 +11,34 @@ fn getpid() {
 fn getppid() {
     assert!(psutil::getppid() != 0)
 }
+
+#[test]
+fn result_alias() {
+    let process: psutil::Result<psutil::Process> = psutil::Process::new(psutil::getpid());
+    assert_eq!(process.unwrap().pid, psutil::getpid());
+
+    let missing: psutil::Result<psutil::Process> = psutil::Process::new(-1);
+    assert_eq!(missing.unwrap_err().kind(), psutil::ErrorKind::NotFound);
+}
+
+#[test]
+fn error_variants() {
+    match psutil::Process::new(-1) {
+        Err(psutil::Error::NoSuchProcess { pid }) => assert_eq!(pid, -1),
+        other => panic!("expected NoSuchProcess, got {:?}", other)
+    }
+
+    let denied = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+    let denied = psutil::Error::for_process(1, denied);
+    assert_eq!(denied.kind(), psutil::ErrorKind::PermissionDenied);
+    assert_eq!(denied.to_string(), "Permission denied for process 1");
+
+    let other = psutil::Error::for_process(1, io::Error::new(io::ErrorKind::Other, "bad stat"));
+    assert_eq!(other.to_string(), "bad stat");
+
+    // Errors convert both ways, so `try!` works in functions returning either
+    let converted: psutil::Error = io::Error::from_raw_os_error(3).into();
+    assert_eq!(converted.raw_os_error(), Some(3));
+    let back: io::Error = psutil::Error::NoSuchProcess { pid: 1 }.into();
+    assert_eq!(back.kind(), io::ErrorKind::NotFound);
+}
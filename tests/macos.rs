@@ -0,0 +1,29 @@
+#![cfg(target_os = "macos")]
+
+extern crate psutil;
+
+use std::io::ErrorKind;
+
+use psutil::process::{Process,State,all,pids};
+
+#[test]
+fn process_self() {
+    let process = Process::new(psutil::getpid()).unwrap();
+    assert_eq!(process.pid, psutil::getpid());
+    assert_eq!(process.ppid, psutil::getppid());
+    assert_eq!(process.state, State::Running);
+    assert!(process.rss > 0 && process.vsize >= process.rss);
+    assert!(process.num_threads >= 1);
+    assert!(!process.comm.is_empty());
+}
+
+#[test]
+fn process_missing() {
+    assert_eq!(Process::new(-1).unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_all() {
+    assert!(pids().unwrap().contains(&psutil::getpid()));
+    assert!(all().iter().any(|p| p.pid == psutil::getpid()));
+}
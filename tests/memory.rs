@@ -0,0 +1,52 @@
+#![cfg(target_os = "linux")]
+
+extern crate psutil;
+
+use psutil::memory::{ThpMode,hugepages,numa_nodes,parse_meminfo,parse_thp_enabled};
+
+#[test]
+fn parse_meminfo_fields() {
+    let meminfo = parse_meminfo(concat!(
+        "MemTotal:        5340920 kB\n",
+        "AnonHugePages:      2048 kB\n",
+        "HugePages_Total:       4\n",
+        "Hugepagesize:       2048 kB\n")).unwrap();
+
+    assert_eq!(meminfo["MemTotal"], 5340920 * 1024);
+    assert_eq!(meminfo["HugePages_Total"], 4);
+    assert_eq!(meminfo["Hugepagesize"], 2 * 1024 * 1024);
+    assert!(parse_meminfo("MemTotal: lots kB\n").is_err());
+}
+
+#[test]
+fn parse_node_meminfo() {
+    let meminfo = parse_meminfo(concat!(
+        "Node 0 MemTotal:        5340920 kB\n",
+        "Node 10 HugePages_Free:      0\n")).unwrap();
+
+    assert_eq!(meminfo["MemTotal"], 5340920 * 1024);
+    assert_eq!(meminfo["HugePages_Free"], 0);
+}
+
+#[test]
+fn parse_thp() {
+    assert_eq!(parse_thp_enabled("always [madvise] never\n").unwrap(), ThpMode::Madvise);
+    assert_eq!(parse_thp_enabled("[always] madvise never\n").unwrap(), ThpMode::Always);
+    assert!(parse_thp_enabled("always madvise never\n").is_err());
+}
+
+#[test]
+fn system_hugepages() {
+    let hugepages = hugepages().unwrap();
+    assert!(hugepages.free <= hugepages.total + hugepages.surplus);
+    assert!(hugepages.total == 0 || hugepages.page_size > 0);
+}
+
+#[test]
+fn system_numa_nodes() {
+    let nodes = numa_nodes().unwrap();
+    assert!(nodes.windows(2).all(|w| w[0].node < w[1].node));
+    for node in nodes {
+        assert!(node.free <= node.total && node.used <= node.total);
+    }
+}
@@ -0,0 +1,110 @@
+#![cfg(target_os = "linux")]
+
+extern crate psutil;
+
+use std::net::{SocketAddr,TcpListener,UdpSocket};
+
+use psutil::getpid;
+use psutil::network::{ConnKind,Protocol,TcpState,listening,net_io_counters,parse_net_dev,parse_net_sockets,process_using_port};
+use psutil::process::Process;
+
+#[test]
+fn network_net_io_counters() {
+    assert!(net_io_counters().unwrap().contains_key("lo"));
+}
+
+#[test]
+fn network_process_net_io_counters() {
+    // The test process shares the namespace of the calling process
+    let mut interfaces: Vec<String> = Process::new(getpid()).unwrap()
+        .net_io_counters().unwrap().keys().cloned().collect();
+    let mut expected: Vec<String> = net_io_counters().unwrap().keys().cloned().collect();
+    interfaces.sort();
+    expected.sort();
+    assert_eq!(interfaces, expected);
+}
+
+#[test]
+fn parse_net_dev_interfaces() {
+    let counters = parse_net_dev(concat!(
+        "Inter-|   Receive                                                |  Transmit\n",
+        " face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n",
+        "    lo: 45851004    3762    0    0    0     0          0         0 45851004    3762    0    0    0     0       0          0\n",
+        "  eth0:1401446     162    1    2    0     0          0         0    18865     172    3    4    0     0       0          0\n")).unwrap();
+
+    let eth0 = counters["eth0"];
+    assert_eq!(counters.len(), 2);
+    assert_eq!((eth0.bytes_recv, eth0.packets_recv, eth0.errin, eth0.dropin), (1401446, 162, 1, 2));
+    assert_eq!((eth0.bytes_sent, eth0.packets_sent, eth0.errout, eth0.dropout), (18865, 172, 3, 4));
+}
+
+#[test]
+fn parse_net_sockets_tcp() {
+    let sockets = parse_net_sockets(concat!(
+        "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n",
+        "   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 24067 1 0000000000000000 100 0 0 10 0\n",
+        "   1: 0100007F:1F90 0100007F:D431 06 00000000:00000000 03:00000A1B 00000000     0        0 0 3 0000000000000000\n"),
+        Protocol::Tcp).unwrap();
+
+    assert_eq!(sockets.len(), 2);
+    assert_eq!(sockets[0].local, "127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    assert_eq!(sockets[0].remote, "0.0.0.0:0".parse::<SocketAddr>().unwrap());
+    assert_eq!(sockets[0].state, TcpState::Listen);
+    assert_eq!((sockets[0].uid, sockets[0].inode, sockets[0].pid), (1000, 24067, None));
+    assert_eq!(sockets[1].remote.port(), 54321);
+    assert_eq!(sockets[1].state, TcpState::TimeWait);
+}
+
+#[test]
+fn parse_net_sockets_tcp6() {
+    let sockets = parse_net_sockets(concat!(
+        "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n",
+        "   0: 00000000000000000000000001000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0\n"),
+        Protocol::Tcp).unwrap();
+
+    assert_eq!(sockets[0].local, "[::1]:22".parse::<SocketAddr>().unwrap());
+}
+
+#[test]
+fn network_process_using_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let process = process_using_port(port, ConnKind::Tcp).unwrap().unwrap();
+    assert_eq!(process.pid, getpid());
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+    let process = process_using_port(port, ConnKind::Udp4).unwrap().unwrap();
+    assert_eq!(process.pid, getpid());
+}
+
+#[test]
+fn network_listening() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let tcp = listener.local_addr().unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let udp = socket.local_addr().unwrap();
+    let sockets = listening().unwrap();
+
+    assert!(sockets.iter().any(|s| s.protocol == Protocol::Tcp && s.local == tcp && s.pid == Some(getpid())));
+    assert!(sockets.iter().any(|s| s.protocol == Protocol::Udp && s.local == udp && s.pid == Some(getpid())));
+    assert!(sockets.iter().all(|s| s.protocol == Protocol::Udp || s.state == TcpState::Listen));
+}
+
+#[test]
+fn network_process_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let _client = std::net::TcpStream::connect(address).unwrap();
+    let (_server, _) = listener.accept().unwrap();
+    let process = Process::new(getpid()).unwrap();
+
+    let tcp = process.connections(ConnKind::Tcp4).unwrap();
+    assert!(tcp.iter().any(|c| c.local == address && c.state == TcpState::Listen));
+    assert!(tcp.iter().all(|c| c.pid == Some(getpid()) && c.protocol == Protocol::Tcp));
+
+    // The client and the accepted side of the same connection
+    let established = process.connections_by_state(&[TcpState::Established]).unwrap();
+    assert!(established.iter().filter(|c| c.local == address || c.remote == address).count() >= 2);
+    assert!(established.iter().all(|c| c.state == TcpState::Established));
+}
@@ -1,12 +1,42 @@
+#![cfg(target_os = "linux")]
+
 extern crate psutil;
+extern crate tempdir;
+
+use std::ffi::OsStr;
+use std::fs::{self,File};
+use std::io::{ErrorKind,Read,Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child,Command,Stdio};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+use tempdir::TempDir;
 
 use psutil::getpid;
-use psutil::process::Process;
+use psutil::process::{Capability,CgroupCpuStat,CpuSet,CpuTimes,FdInfo,Memory,MemoryFull,MemoryMap,MountInfo,NumaMap,PeakTracker,Process,ProcessInfoBuilder,ProcessIoCollector,RLimit,SeccompMode,Signal,SmapsEntry,SortKey,State,Status,Thread};
 
 fn get_process() -> Process {
     Process::new(getpid()).unwrap()
 }
 
+/// Wait for a spawned child to replace the forked test process with `comm`
+fn wait_for_exec(child: &Child, comm: &str) -> Process {
+    for _ in 0..100 {
+        let process = Process::new(child.id() as psutil::PID).unwrap();
+        if process.comm == comm {
+            return process;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("child did not exec {}", comm);
+}
+
 #[test]
 fn process() {
     assert!(Process::new(getpid()).is_ok());
@@ -47,3 +77,1541 @@ fn process_inequality() {
     assert!(get_process() != Process::new(1).unwrap());
 }
 
+
+#[test]
+fn process_open_regular_files() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().join("process_open_regular_files.txt");
+    write!(&mut File::create(&path).unwrap(), "{}", "beans").unwrap();
+
+    let mut file = File::open(&path).unwrap();
+    file.read_exact(&mut [0; 2]).unwrap();
+
+    let files = get_process().open_regular_files().unwrap();
+    let open = files.iter().find(|f| f.path == path).unwrap();
+    assert_eq!(open.position, 2);
+    assert_eq!(open.mode, "r");
+    assert!(files.iter().all(|f| f.path.is_absolute()));
+}
+
+#[test]
+fn process_fd_summary() {
+    let process = get_process();
+    let before = process.fd_summary().unwrap();
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let _file = File::create(tempdir.path().join("fd_summary")).unwrap();
+    let (_reader, _writer) = std::os::unix::net::UnixStream::pair().unwrap();
+    let after = process.fd_summary().unwrap();
+
+    // Other tests run in parallel and open files of their own
+    assert!(after.files >= 1);
+    assert!(after.sockets >= 2);
+    assert!(before.total() > 0);
+}
+
+#[test]
+fn process_disk_usage_of_open_files() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().join("disk_usage");
+    File::create(&path).unwrap().write_all(&[1; 64 * 1024]).unwrap();
+
+    let _first = File::open(&path).unwrap();
+    let _second = File::open(&path).unwrap();
+    let file = std::os::unix::fs::MetadataExt::blocks(&fs::metadata(&path).unwrap()) * 512;
+    let total = get_process().disk_usage_of_open_files().unwrap();
+
+    assert!(total >= file);
+}
+
+#[test]
+fn process_deleted_open_files() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().join("deleted_open_file");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(&[1; 1000]).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let deleted = get_process().deleted_open_files().unwrap();
+    assert!(deleted.contains(&(file.as_raw_fd(), path, 1000)));
+}
+
+#[test]
+fn process_all_open_files() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().join("all_open_files");
+    let file = File::create(&path).unwrap();
+    let matches = |files: &[(psutil::PID, psutil::process::OpenFile)]| {
+        files.iter().any(|&(pid, ref f)| pid == getpid() && f.fd == file.as_raw_fd() && f.path == path)
+    };
+
+    assert!(matches(&psutil::process::all_open_files().unwrap()));
+
+    let under = psutil::process::open_files_under(tempdir.path()).unwrap();
+    assert!(matches(&under));
+    assert!(under.iter().all(|&(_, ref f)| f.path.starts_with(tempdir.path())));
+}
+
+#[test]
+fn process_fdinfo() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().join("process_fdinfo.txt");
+    write!(&mut File::create(&path).unwrap(), "{}", "beans").unwrap();
+
+    let mut file = File::open(&path).unwrap();
+    file.read_exact(&mut [0; 3]).unwrap();
+
+    let fdinfo = get_process().fdinfo(file.as_raw_fd()).unwrap();
+    assert_eq!(fdinfo.pos, 3);
+}
+
+#[test]
+fn parse_fdinfo_epoll() {
+    let fdinfo: FdInfo = FromStr::from_str(concat!(
+        "pos:\t0\n",
+        "flags:\t02\n",
+        "mnt_id:\t15\n",
+        "tfd:        5 events:       1d data: ffffffffffffffff  pos:0 ino:61af sdev:7\n")).unwrap();
+
+    assert_eq!(fdinfo.flags, 2);
+    assert_eq!(fdinfo.mnt_id, Some(15));
+
+    let tfd = fdinfo.entry("tfd").unwrap();
+    assert_eq!(tfd.get("tfd"), Some("5"));
+    assert_eq!(tfd.get("events"), Some("1d"));
+    assert_eq!(tfd.get("ino"), Some("61af"));
+}
+
+#[test]
+fn process_processes_of_user() {
+    let processes = psutil::process::processes_of_user(get_process().uid).unwrap();
+    assert!(processes.iter().any(|p| p.pid == getpid()));
+}
+
+#[test]
+fn process_processes_of_unknown_username() {
+    let result = psutil::process::processes_of_username("psutil-no-such-user");
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_peak_tracker() {
+    let mut small = get_process();
+    small.rss = 1;
+    small.vsize = 4096;
+
+    let mut large = small.clone();
+    large.rss = 2;
+    large.vsize = 8192;
+
+    let start = UNIX_EPOCH + Duration::from_secs(1);
+    let end = UNIX_EPOCH + Duration::from_secs(2);
+
+    let mut tracker = PeakTracker::with_page_size(4096);
+    assert_eq!(tracker.peak_rss_time(), None);
+
+    tracker.update_at(&large, start);
+    tracker.update_at(&small, end);
+    assert_eq!(tracker.peak_vsz(), 8192);
+    assert_eq!(tracker.peak_vsz_time(), Some(start));
+    assert_eq!(tracker.peak_rss_time(), Some(start));
+    assert_eq!(tracker.peak_rss(), 2 * 4096);
+
+    let mut single = PeakTracker::new();
+    single.update_at(&small, end);
+    assert_eq!(tracker.peak_rss(), 2 * single.peak_rss());
+}
+
+#[test]
+fn process_pids() {
+    let pids = psutil::process::pids().unwrap();
+    assert!(pids.contains(&getpid()));
+    assert!(psutil::process::pid_count().unwrap() > 0);
+}
+
+#[test]
+fn process_top() {
+    let by_rss = psutil::process::top(5, SortKey::Rss).unwrap();
+    assert!(by_rss.len() <= 5);
+    assert!(by_rss.windows(2).all(|w| w[0].rss >= w[1].rss));
+
+    let by_cpu = psutil::process::top(3, SortKey::CpuPercent).unwrap();
+    assert!(!by_cpu.is_empty() && by_cpu.len() <= 3);
+}
+
+#[test]
+fn process_new_many() {
+    let results = psutil::process::new_many(&[getpid(), 0, getpid()]);
+    assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), vec![getpid(), 0, getpid()]);
+    assert_eq!(results[0].1.as_ref().unwrap(), &get_process());
+    assert_eq!(results[1].1.as_ref().unwrap_err().kind(), ErrorKind::NotFound);
+    assert!(results[2].1.is_ok());
+}
+
+#[test]
+fn process_threads() {
+    let (sender, receiver) = mpsc::channel::<()>();
+    let handle = thread::spawn(move || receiver.recv().unwrap_or(()));
+
+    let threads = get_process().threads().unwrap();
+    let all = psutil::process::all_threads().unwrap();
+    sender.send(()).unwrap();
+    handle.join().unwrap();
+
+    assert!(threads.len() >= 2);
+    assert!(threads.iter().all(|t| t.pid == getpid()));
+    assert!(threads.iter().any(|t| t.tid == getpid()));
+    assert!(all.iter().any(|t| t.pid == getpid() && t.tid == getpid()));
+}
+
+#[test]
+fn process_cpu_affinity() {
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    let before = process.get_cpu_affinity().unwrap();
+    process.set_cpu_affinity(&before[..1]).unwrap();
+    let after = process.get_cpu_affinity().unwrap();
+    let empty = process.set_cpu_affinity(&[]).unwrap_err();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(!before.is_empty());
+    assert_eq!(after, &before[..1]);
+    assert_eq!(empty.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn thread_name_and_affinity() {
+    let thread_self = fs::read_link("/proc/thread-self").unwrap();
+    let tid = thread_self.file_name().unwrap().to_string_lossy().parse().unwrap();
+    let thread = Thread::new(getpid(), tid).unwrap();
+
+    write!(&mut File::create("/proc/thread-self/comm").unwrap(), "{}", "psutil-thread").unwrap();
+    assert_eq!(thread.name().unwrap(), "psutil-thread");
+
+    let affinity = thread.get_cpu_affinity().unwrap();
+    thread.set_cpu_affinity(&affinity).unwrap();
+    assert_eq!(thread.get_cpu_affinity().unwrap(), affinity);
+}
+
+#[test]
+fn process_info_builder() {
+    let info = ProcessInfoBuilder::new().collect(getpid()).unwrap();
+    assert_eq!(info.process, get_process());
+    assert!(info.status.is_none() && info.memory.is_none());
+    assert!(info.io.is_none() && info.cmdline.is_none());
+
+    let info = ProcessInfoBuilder::new().with_status().with_memory().with_cmdline()
+        .collect(getpid()).unwrap();
+    assert_eq!(info.status.unwrap().pid, getpid());
+    assert!(info.memory.unwrap().resident > 0);
+    assert!(info.io.is_none());
+    assert_eq!(info.cmdline, get_process().cmdline_vec().unwrap());
+}
+
+#[test]
+fn process_io_collector() {
+    let mut collector = ProcessIoCollector::new(getpid()).unwrap();
+    let rates = collector.poll().unwrap();
+    assert!(rates.read_bytes_per_sec >= 0.0 && rates.write_bytes_per_sec >= 0.0);
+
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let mut collector = ProcessIoCollector::new(child.id() as psutil::PID).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(collector.poll().unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_is_descendant_of() {
+    let process = get_process();
+    assert!(process.is_descendant_of(process.ppid).unwrap());
+    assert!(process.is_descendant_of(1).unwrap() || process.ppid == 0);
+    assert!(!process.is_descendant_of(process.pid).unwrap());
+}
+
+#[test]
+fn process_kill_tree() {
+    let mut child = Command::new("sh").arg("-c").arg("sleep 30 & sleep 30").spawn().unwrap();
+    let process = Process::new(child.id() as psutil::PID).unwrap();
+
+    // Wait for the shell to start both of its children
+    let mut attempts = 0;
+    while process.children().unwrap().len() < 2 && attempts < 100 {
+        thread::sleep(Duration::from_millis(10));
+        attempts += 1;
+    }
+
+    let descendants = process.descendants().unwrap();
+    assert_eq!(descendants.len(), 2);
+
+    let signalled = process.kill_tree(Signal::Kill).unwrap();
+    assert_eq!(signalled[0], process.pid);
+    assert!(descendants.iter().all(|d| signalled.contains(&d.pid)));
+    assert!(!child.wait().unwrap().success());
+}
+
+#[test]
+fn process_find_by_cmdline() {
+    let mut child = Command::new("sleep").arg("30.4101").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    let found = psutil::process::find_by_cmdline("sleep 30.4101").unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(found.contains(&process));
+}
+
+#[test]
+fn process_set_nice() {
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    process.set_nice(5).unwrap();
+    let after_set = Process::new(process.pid).unwrap().nice;
+    process.set_nice_relative(100).unwrap();
+    let after_relative = Process::new(process.pid).unwrap().nice;
+    let out_of_range = process.set_nice(20).unwrap_err().kind();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(after_set, 5);
+    assert_eq!(after_relative, 19);
+    assert_eq!(out_of_range, ErrorKind::InvalidInput);
+}
+
+#[test]
+fn process_wait_for_state() {
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    process.send_signal(Signal::Stop).unwrap();
+    let stopped = process.wait_for_state(State::Traced, Duration::from_secs(5)).unwrap();
+    let timed_out = process.wait_for_state(State::Running, Duration::from_millis(50)).unwrap();
+
+    child.kill().unwrap();
+    let exited = process.wait_for_state(State::Paging, Duration::from_secs(5)).unwrap_err();
+    child.wait().unwrap();
+
+    assert!(stopped);
+    assert!(!timed_out);
+    assert_eq!(exited.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_memory_tree() {
+    let mut child = Command::new("sh").arg("-c").arg("sleep 30 & sleep 30").spawn().unwrap();
+    let process = wait_for_exec(&child, "sh");
+    while process.descendants().unwrap().len() < 2 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let own = process.memory().unwrap();
+    let tree = process.memory_tree().unwrap();
+    let own_full = process.memory_full_rollup().unwrap();
+    let tree_full = process.memory_full_tree().unwrap();
+
+    process.kill_tree(Signal::Kill).unwrap();
+    child.wait().unwrap();
+
+    assert!(tree.resident > own.resident);
+    assert!(tree_full.pss > own_full.pss);
+}
+
+#[test]
+fn process_comm_live() {
+    let script = "printf psutil-live > /proc/self/comm; sleep 30; true";
+    let mut child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+    let process = wait_for_exec(&child, "sh");
+
+    let mut comm = process.comm_live().unwrap();
+    for _ in 0..100 {
+        if comm != "sh" {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+        comm = process.comm_live().unwrap();
+    }
+
+    process.kill_tree(Signal::Kill).unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(process.comm, "sh");
+    assert_eq!(comm, "psutil-live");
+}
+
+#[test]
+fn process_environ_large() {
+    // Larger than the initial 4096 byte read buffer, so the file is read again
+    let value: String = (0..1000).map(|i| format!("{:>9},", i)).collect();
+    let mut child = Command::new("sleep").arg("30").env("PSUTIL_TEST", &value).spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    let expected = (b"PSUTIL_TEST".to_vec(), value.into_bytes());
+
+    let mut found = false;
+    for _ in 0..100 {
+        found = process.environ_raw().unwrap().contains(&expected);
+        if found {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // The grown buffer is reused for smaller files
+    assert_eq!(process.status().unwrap().pid, process.pid);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(found);
+}
+
+#[test]
+fn process_environ_raw() {
+    let value = OsStr::from_bytes(b"caf\xe9=beans");
+    let mut child = Command::new("sleep").arg("30").env("PSUTIL_TEST", value).spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    let expected = (b"PSUTIL_TEST".to_vec(), b"caf\xe9=beans".to_vec());
+
+    // The environment is only copied into place after the name has changed
+    let mut found = false;
+    for _ in 0..100 {
+        found = process.environ_raw().unwrap().contains(&expected);
+        if found {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    process.kill().unwrap();
+    child.wait().unwrap();
+    assert!(found);
+}
+
+#[test]
+fn process_cmdline_bytes() {
+    let name = OsStr::from_bytes(b"caf\xe9 beans");
+    let mut child = Command::new("sh").arg("-c").arg("sleep 30; exit").arg(name).spawn().unwrap();
+    let process = wait_for_exec(&child, "sh");
+    let expected: Vec<Vec<u8>> = vec![
+        b"sh".to_vec(), b"-c".to_vec(), b"sleep 30; exit".to_vec(), b"caf\xe9 beans".to_vec()];
+
+    let mut cmdline = None;
+    for _ in 0..100 {
+        cmdline = process.cmdline_bytes().unwrap();
+        if cmdline.as_ref() == Some(&expected) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    process.kill_tree(Signal::Kill).unwrap();
+    child.wait().unwrap();
+    assert_eq!(cmdline, Some(expected));
+}
+
+#[test]
+fn process_exe() {
+    assert_eq!(get_process().exe().unwrap().unwrap(), std::env::current_exe().unwrap());
+}
+
+/// Find kthreadd or one of the kernel threads it started
+///
+/// kthreadd is usually PID 2, but isn't visible at all in a PID namespace.
+fn kernel_thread() -> Option<Process> {
+    let processes = psutil::process::all();
+    let kthreadd = match processes.iter().find(|p| p.comm == "kthreadd" && p.ppid == 0) {
+        Some(kthreadd) => kthreadd.pid,
+        None => return None
+    };
+    processes.into_iter().find(|p| p.pid == kthreadd || p.ppid == kthreadd)
+}
+
+#[test]
+fn process_missing_files() {
+    match kernel_thread() {
+        Some(kthread) => {
+            assert_eq!(kthread.exe().unwrap(), None);
+            assert_eq!(kthread.cmdline().unwrap(), None);
+        },
+        None => println!("No kernel threads are visible, skipping them")
+    }
+
+    let mut child = Command::new("true").spawn().unwrap();
+    let zombie = Process::new(child.id() as psutil::PID).unwrap();
+    assert!(zombie.wait_for_state(State::Zombie, Duration::from_secs(5)).unwrap());
+    let cwd = zombie.cwd().unwrap();
+    child.wait().unwrap();
+    let exited = zombie.exe().unwrap_err();
+
+    assert_eq!(cwd, None);
+    assert_eq!(exited.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_io() {
+    let mut file = File::open("/proc/self/stat").unwrap();
+    file.read_to_end(&mut Vec::new()).unwrap();
+
+    let io = get_process().io().unwrap().unwrap();
+    assert!(io.rchar > 0);
+    assert!(io.syscr > 0);
+}
+
+#[test]
+fn process_cwd_deleted() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let path = tempdir.path().to_path_buf();
+    let mut child = Command::new("sleep").arg("30").current_dir(&path).spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    let before = process.cwd().unwrap().unwrap();
+    tempdir.close().unwrap();
+    let raw = process.cwd_raw().unwrap().unwrap();
+    let after = process.cwd().unwrap().unwrap();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(before, path);
+    assert_eq!(raw.to_string_lossy(), format!("{} (deleted)", path.display()));
+    assert_eq!(after, path);
+}
+
+#[test]
+fn process_audit_ids() {
+    let process = get_process();
+    let loginuid = fs::read_to_string("/proc/self/loginuid").unwrap();
+    let expected = match loginuid.trim() {
+        "4294967295" => None,
+        uid => Some(uid.parse().unwrap())
+    };
+
+    assert_eq!(process.loginuid().unwrap(), expected);
+    assert!(process.audit_sessionid().is_ok());
+}
+
+#[test]
+fn process_age() {
+    let process = get_process();
+    assert!(process.create_time().unwrap() <= SystemTime::now() + Duration::from_secs(1));
+    assert!(process.age().unwrap() < Duration::from_secs(24 * 60 * 60));
+}
+
+#[test]
+fn process_send_signal_invalid_pid() {
+    let mut process = get_process();
+    process.pid = 0;
+    assert_eq!(process.send_signal(Signal::Kill).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn process_signal_process_group() {
+    let mut child = Command::new("sleep").arg("30").process_group(0).spawn().unwrap();
+    psutil::process::signal_process_group(child.id() as psutil::PID, Signal::Kill).unwrap();
+    assert!(!child.wait().unwrap().success());
+}
+
+#[test]
+fn process_signal_many() {
+    let mut running = Command::new("sleep").arg("30").spawn().unwrap();
+    let mut exited = Command::new("true").spawn().unwrap();
+    let exited_pid = exited.id() as psutil::PID;
+    exited.wait().unwrap();
+
+    let pids = [running.id() as psutil::PID, exited_pid, 0];
+    let results = psutil::process::signal_many(&pids, Signal::Kill);
+    running.wait().unwrap();
+
+    assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), pids.to_vec());
+    assert_eq!(results[0].1.as_ref().unwrap(), &true);
+    assert_eq!(results[1].1.as_ref().unwrap(), &false);
+    assert_eq!(results[2].1.as_ref().unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn process_watch() {
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let pid = child.id() as psutil::PID;
+    wait_for_exec(&child, "sleep");
+
+    let watch = psutil::process::watch(pid);
+    assert!(watch.recv_timeout(Duration::from_millis(200)).is_err());
+
+    child.kill().unwrap();
+    let event = watch.recv_timeout(Duration::from_secs(5)).unwrap();
+    child.wait().unwrap();
+    assert_eq!(event.pid, pid);
+
+    let exited = psutil::process::watch(pid);
+    assert_eq!(exited.recv_timeout(Duration::from_secs(5)).unwrap().pid, pid);
+}
+
+#[test]
+fn process_same_pid() {
+    let process = get_process();
+    let mut reused = get_process();
+    reused.starttime += 1;
+
+    assert!(process.same_pid(&reused));
+    assert!(!process.same_identity(&reused));
+    assert!(process.same_identity(&get_process()));
+}
+
+#[test]
+fn process_self() {
+    assert_eq!(psutil::process::self_exe().unwrap(), std::env::current_exe().unwrap());
+    assert_eq!(psutil::process::self_cwd().unwrap(), std::env::current_dir().unwrap());
+    assert_eq!(psutil::process::self_cmdline().unwrap(), get_process().cmdline().unwrap());
+}
+
+#[test]
+fn process_status() {
+    let mut process = get_process();
+    let status = process.status().unwrap();
+    assert_eq!(status.pid, getpid());
+    assert_eq!(status.uid.effective, process.uid);
+    assert!(status.vm_rss.unwrap() > 0);
+
+    assert_eq!(process.status_cached().unwrap().name, status.name);
+}
+
+#[test]
+fn parse_status_kernel_thread() {
+    let status: Status = FromStr::from_str(concat!(
+        "Name:\tkthreadd\n",
+        "State:\tS (sleeping)\n",
+        "Tgid:\t2\n",
+        "Pid:\t2\n",
+        "PPid:\t0\n",
+        "TracerPid:\t0\n",
+        "Uid:\t0\t0\t0\t0\n",
+        "Gid:\t0\t0\t0\t0\n",
+        "Threads:\t1\n",
+        "voluntary_ctxt_switches:\t1178\n",
+        "nonvoluntary_ctxt_switches:\t3\n")).unwrap();
+
+    assert_eq!(status.name, "kthreadd");
+    assert_eq!(status.ppid, 0);
+    assert_eq!(status.vm_rss, None);
+    assert_eq!(status.voluntary_ctxt_switches, 1178);
+}
+
+#[test]
+fn parse_capabilities() {
+    assert_eq!(Capability::from_mask(0x3000), vec![Capability::NetAdmin, Capability::NetRaw]);
+    assert_eq!(Capability::from_mask(1 << 63), vec![Capability::Unknown(63)]);
+    assert_eq!(Capability::SysAdmin.number(), 21);
+    assert_eq!(Capability::from_number(40), Capability::CheckpointRestore);
+}
+
+#[test]
+fn process_capabilities() {
+    let process = get_process();
+    let capabilities = process.capabilities().unwrap();
+    let status = process.status().unwrap();
+    assert_eq!(capabilities.effective.len() as u32, status.cap_eff.unwrap().count_ones());
+}
+
+#[test]
+fn process_seccomp_mode() {
+    let process = get_process();
+    let expected = match process.status().unwrap().seccomp {
+        Some(2) => SeccompMode::Filter,
+        _ => SeccompMode::Disabled
+    };
+    assert_eq!(process.seccomp_mode().unwrap(), expected);
+}
+
+#[test]
+fn process_no_new_privs() {
+    extern "C" {
+        fn prctl(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i32;
+    }
+    const PR_SET_NO_NEW_PRIVS: i32 = 38;
+
+    let mut command = Command::new("sleep");
+    command.arg("30");
+    unsafe {
+        command.pre_exec(|| match prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error())
+        });
+    }
+    let mut child = command.spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    let no_new_privs = process.no_new_privs().unwrap();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(no_new_privs);
+}
+
+#[test]
+fn parse_signal_mask() {
+    assert_eq!(Signal::from_mask(0x4100), vec![Signal::Kill, Signal::Terminate]);
+    assert_eq!(Signal::from_mask(1 << 40), vec![]);
+}
+
+#[test]
+fn process_signal_masks() {
+    // The Rust runtime ignores SIGPIPE before main
+    assert!(get_process().signal_masks().unwrap().ignored.contains(&Signal::Pipe));
+}
+
+#[test]
+fn process_is_running_as() {
+    let mut process = get_process();
+    let uid = process.status().unwrap().uid.effective;
+    assert!(process.is_running_as(uid));
+    assert!(!process.is_running_as(uid + 1));
+    assert_eq!(process.is_running_as_root(), uid == 0);
+
+    process.status_cached().unwrap();
+    assert!(process.is_running_as(uid));
+}
+
+#[test]
+fn process_send_signal_exited() {
+    let mut child = Command::new("true").spawn().unwrap();
+    let process = Process::new(child.id() as psutil::PID).unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(process.send_signal(Signal::Terminate).unwrap(), false);
+}
+
+#[test]
+fn process_memory_zombie() {
+    let mut child = Command::new("true").spawn().unwrap();
+    let pid = child.id() as psutil::PID;
+
+    // The child stays a zombie until it is waited for
+    let mut zombie = None;
+    for _ in 0..100 {
+        let process = Process::new(pid).unwrap();
+        if let State::Zombie = process.state {
+            zombie = Some(process);
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let memory = zombie.expect("child did not become a zombie").memory().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(memory.size, 0);
+    assert_eq!(memory.resident, 0);
+    assert_eq!(memory.data, 0);
+}
+
+/// Names can contain spaces and brackets, which must not confuse the parser
+#[test]
+fn process_pathological_comm() {
+    // Only the current thread is renamed, as each test runs in its own thread
+    let thread_self = fs::read_link("/proc/thread-self").unwrap();
+    let tid = thread_self.file_name().unwrap().to_string_lossy().parse().unwrap();
+    write!(&mut File::create("/proc/thread-self/comm").unwrap(), "{}", "a) b (c )").unwrap();
+
+    let thread = Process::new(tid).unwrap();
+    assert_eq!(thread.comm, "a) b (c )");
+    assert_eq!(thread.pid, tid);
+    assert_eq!(thread.ppid, get_process().ppid);
+}
+
+#[test]
+fn process_exited_errors() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let pid = process.pid;
+    let gone = |error: psutil::Error| match error {
+        psutil::Error::NoSuchProcess { pid: p } => assert_eq!(p, pid),
+        other => panic!("expected NoSuchProcess, got {:?}", other)
+    };
+    gone(process.exe().unwrap_err());
+    gone(process.cwd().unwrap_err());
+    gone(process.num_fds().unwrap_err());
+    gone(process.threads().unwrap_err());
+    gone(process.get_rlimit(RLimit::NumFiles).unwrap_err());
+}
+
+#[test]
+fn process_non_utf8_comm() {
+    // The shell renames itself and then waits, so its name stays invalid UTF-8
+    let mut child = Command::new("sh")
+        .arg("-c").arg("printf 'bad\\377\\376' > /proc/$$/comm; sleep 10").spawn().unwrap();
+    let process = wait_for_exec(&child, "bad\u{fffd}\u{fffd}");
+
+    assert!(psutil::process::all_basic().unwrap().iter().any(|&(ref p, _)| p.pid == process.pid));
+    assert!(psutil::process::all().iter().any(|p| p.pid == process.pid));
+    assert!(process.stat_raw().unwrap()[1].starts_with("bad"));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+const STAT: &'static str = concat!(
+    "1234 (test) S 1 1234 1234 0 -1 4194560 100 0 0 0 250 125 0 0 20 0 1 0 500 ",
+    "1000000 100 18446744073709551615 0 0 0 0 0 0 0 0 0 0 0 0 17 0 0 0 0 0 0 0 0 ",
+    "0 0 0 0 0 0\n");
+
+#[test]
+fn parse_stat_with_ticks_per_second() {
+    let process = Process::from_stat(STAT, 1000, 1000, 100).unwrap();
+    assert_eq!(process.pid, 1234);
+    assert_eq!(process.comm, "test");
+    assert_eq!(process.utime, 2.5);
+    assert_eq!(process.stime, 1.25);
+
+    let process = Process::from_stat(STAT, 1000, 1000, 250).unwrap();
+    assert_eq!(process.utime, 1.0);
+}
+
+#[test]
+fn process_cpu_energy_share() {
+    // Two packages using 3 and 1 joules, the second wrapping around at 10 J
+    let before = [(1_000_000, 100_000_000), (9_500_000, 10_000_000)];
+    let after = [(4_000_000, 100_000_000), (500_000, 10_000_000)];
+    let share = |process, busy| psutil::process::cpu_energy_share(&before, &after, process, busy);
+
+    assert_eq!(share(1.0, 4.0), 1.0);
+    assert_eq!(share(8.0, 4.0), 4.0);
+    assert_eq!(share(-1.0, 4.0), 0.0);
+    assert_eq!(share(1.0, 0.0), 0.0);
+    assert_eq!(psutil::process::cpu_energy_share(&[], &[], 1.0, 1.0), 0.0);
+}
+
+#[test]
+fn parse_stat_pathological_comm() {
+    // The kernel writes the name as it was set, so it can contain newlines,
+    // other control bytes and a closing parenthesis of its own
+    let stat = STAT.replace("(test)", "(a\nb\x01c))");
+    let process = Process::from_stat(&stat, 1000, 1000, 100).unwrap();
+    assert_eq!(process.comm, "a\nb\x01c)");
+    assert_eq!(process.state, State::Sleeping);
+    assert_eq!(process.ppid, 1);
+    assert_eq!(process.utime, 2.5);
+}
+
+#[test]
+fn parse_stat_blkio_delay_with_ticks_per_second() {
+    let mut fields: Vec<&str> = STAT.split_whitespace().collect();
+    fields[41] = "250";
+    let stat = fields.join(" ");
+
+    let delay = |ticks| Process::from_stat(&stat, 1000, 1000, ticks).unwrap().blkio_delay();
+    assert_eq!(delay(100), Duration::from_millis(2500));
+    assert_eq!(delay(1000), Duration::from_millis(250));
+    assert_eq!(delay(0), Duration::from_secs(0));
+}
+
+#[test]
+fn parse_stat_start_time_with_ticks_per_second() {
+    // starttime is 500 ticks, which is 5 seconds at 100 and 0.5 at 1000
+    let slow = Process::from_stat(STAT, 1000, 1000, 100).unwrap().create_time().unwrap();
+    let fast = Process::from_stat(STAT, 1000, 1000, 1000).unwrap().create_time().unwrap();
+    assert_eq!(slow.duration_since(fast).unwrap(), Duration::from_millis(4500));
+}
+
+#[test]
+fn parse_stat_cpu_times_precision() {
+    // Weeks of CPU time at a high tick rate, where an f64 of seconds can no
+    // longer tell neighbouring ticks apart
+    let before = STAT.replace(" 250 125 ", " 9007199254740993 0 ");
+    let after = STAT.replace(" 250 125 ", " 9007199254740995 0 ");
+    let before = Process::from_stat(&before, 1000, 1000, 1).unwrap();
+    let after = Process::from_stat(&after, 1000, 1000, 1).unwrap();
+    assert!(after.utime - before.utime != 2.0);
+
+    let (before, after) = (before.cpu_times(), after.cpu_times());
+    assert_eq!(after.ticks_since(&before), 2);
+    assert_eq!(after.percent_since(&before, Duration::from_secs(4)), 50.0);
+    assert_eq!(before.ticks_since(&after), 0);
+}
+
+#[test]
+fn parse_stat_cpu_times_breakdown() {
+    let before = Process::from_stat(STAT, 1000, 1000, 100).unwrap().cpu_times();
+    let after = CpuTimes { user: 450, system: 175, children_user: 50, ..before };
+
+    let percent = after.percent_breakdown_since(&before, Duration::from_secs(4));
+    assert_eq!(percent.user_percent, 50.0);
+    assert_eq!(percent.system_percent, 12.5);
+    assert_eq!(percent.children_user_percent, 12.5);
+    assert_eq!(percent.children_system_percent, 0.0);
+}
+
+#[test]
+fn process_cpu_percent() {
+    let percent = get_process().cpu_percent(Duration::from_millis(50)).unwrap();
+    assert!(percent >= 0.0);
+
+    let normalized = get_process().cpu_percent_normalized(Duration::from_millis(50)).unwrap();
+    assert!(normalized >= 0.0 && normalized <= 100.0);
+
+    let breakdown = get_process().cpu_times_percent(Duration::from_millis(50)).unwrap();
+    assert!(breakdown.user_percent >= 0.0 && breakdown.system_percent >= 0.0);
+}
+
+#[test]
+fn process_diff() {
+    let process = |pid, starttime| {
+        let stat = STAT.replacen("1234", pid, 1).replace(" 500 ", &format!(" {} ", starttime));
+        Process::from_stat(&stat, 1000, 1000, 100).unwrap()
+    };
+
+    let old = vec![process("1", 10), process("2", 20), process("3", 30)];
+    let new = vec![process("3", 30), process("2", 25), process("4", 40)];
+    let diff = psutil::process::diff(&old, &new);
+
+    let pids = |processes: &[Process]| processes.iter().map(|p| p.pid).collect::<Vec<_>>();
+    assert_eq!(pids(&diff.started), vec![2, 4]);
+    assert_eq!(pids(&diff.exited), vec![1, 2]);
+    assert_eq!(diff.persisted.len(), 1);
+    assert_eq!(diff.persisted[0].0.pid, 3);
+}
+
+#[test]
+fn parse_mountinfo() {
+    let line = "36 35 98:0 /mnt1 /mnt/with\\040space rw,noatime master:1 shared:2 - ext3 /dev/root rw,errors=continue";
+    let mount = MountInfo::from_str(line).unwrap();
+    assert_eq!(mount.mount_id, 36);
+    assert_eq!(mount.parent_id, 35);
+    assert_eq!((mount.major, mount.minor), (98, 0));
+    assert_eq!(mount.root, PathBuf::from("/mnt1"));
+    assert_eq!(mount.mount_point, PathBuf::from("/mnt/with space"));
+    assert_eq!(mount.options, "rw,noatime");
+    assert_eq!(mount.optional_fields, vec!["master:1", "shared:2"]);
+    assert_eq!(mount.fs_type, "ext3");
+    assert_eq!(mount.source, "/dev/root");
+    assert_eq!(mount.super_options, "rw,errors=continue");
+
+    let line = "25 1 0:22 / /proc rw - proc proc rw";
+    assert!(MountInfo::from_str(line).unwrap().optional_fields.is_empty());
+    assert!(MountInfo::from_str("25 1 0:22 / /proc rw proc proc rw").is_err());
+}
+
+#[test]
+fn process_identity_check() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let mut process = wait_for_exec(&child, "sleep");
+
+    // Pretend the PID now belongs to a process started at a different time
+    process.starttime += 1;
+    assert_eq!(process.kill().unwrap(), false);
+    assert_eq!(process.set_nice(10).unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(process.set_cpu_affinity(&[0]).unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(process.set_rlimit(RLimit::Core, 0, 0).unwrap_err().kind(), ErrorKind::NotFound);
+    assert!(child.try_wait().unwrap().is_none());
+
+    process.set_identity_check(false);
+    assert!(process.kill().unwrap());
+    child.wait().unwrap();
+}
+
+/// Start a thread named with `set_thread_name`, and call `f` with its TID
+///
+/// The thread is listed in `threads` under its new name, and keeps running
+/// until `f` returns.
+fn with_named_thread<F: FnOnce(psutil::PID)>(name: &'static str, f: F) {
+    let (sender, receiver) = mpsc::channel();
+    let (done, wait) = mpsc::channel::<()>();
+
+    let handle = thread::spawn(move || {
+        sender.send(psutil::process::set_thread_name(name)).unwrap();
+        wait.recv().unwrap();
+    });
+
+    receiver.recv().unwrap().unwrap();
+    let thread = get_process().threads().unwrap().into_iter()
+        .find(|t| t.comm == name).unwrap();
+    f(thread.tid);
+    done.send(()).unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn process_set_thread_name() {
+    with_named_thread("psutil-renamed", |tid| {
+        assert_eq!(Process::new(tid).unwrap().comm, "psutil-renamed");
+    });
+
+    let error = psutil::process::set_thread_name("a-name-over-15-bytes").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn process_blocked_in() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    assert!(process.wait_for_state(State::Sleeping, Duration::from_secs(5)).unwrap());
+
+    // Either source may be hidden, but a symbol never includes the offset
+    if let Some(function) = process.blocked_in().unwrap() {
+        assert!(!function.is_empty());
+        assert!(!function.contains("+0x"));
+    }
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_deprioritize() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    // A cgroup of its own, so that the cgroup the tests run in isn't changed
+    let cgroup = TempDir::new("cgroup").unwrap();
+    File::create(cgroup.path().join("cpu.weight")).unwrap();
+
+    process.deprioritize_in(Some(cgroup.path())).unwrap();
+    assert_eq!(Process::new(process.pid).unwrap().nice, 19);
+
+    let mut weight = String::new();
+    File::open(cgroup.path().join("cpu.weight")).unwrap().read_to_string(&mut weight).unwrap();
+    assert_eq!(weight, "1");
+
+    // A cgroup without the cpu controller is skipped
+    let empty = TempDir::new("cgroup").unwrap();
+    process.deprioritize_in(Some(empty.path())).unwrap();
+    process.deprioritize_in(None).unwrap();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_new_verbose() {
+    assert_eq!(Process::new_verbose(getpid()).unwrap(), get_process());
+
+    match Process::new_verbose(-1) {
+        Err(psutil::Error::NoSuchProcess { pid }) => assert_eq!(pid, -1),
+        other => panic!("expected NoSuchProcess, got {:?}", other)
+    }
+}
+
+#[test]
+fn parse_stat_verbose_error() {
+    let truncated = STAT.replace(" 0 0 0 0 0 0\n", "\n");
+    let error = Process::from_stat_verbose(&truncated, 1000, 1000, 100).unwrap_err();
+    let message = error.to_string();
+
+    assert_eq!(error.kind(), ErrorKind::Other);
+    assert!(message.starts_with("Unexpected number of fields"));
+    assert!(message.lines().any(|l| l.contains("starttime") && l.contains("\"500\"")));
+    assert!(message.lines().any(|l| l.contains("exit_code") && l.contains("<missing>")));
+
+    let bad_state = STAT.replace(" S 1 ", " ? 1 ");
+    let message = Process::from_stat_verbose(&bad_state, 1000, 1000, 100).unwrap_err().to_string();
+    assert!(message.lines().any(|l| l.contains("state") && l.contains("could not parse")));
+
+    let mut fields: Vec<&str> = STAT.split_whitespace().collect();
+    fields[42] = "x";
+    let bad_guest_time = fields.join(" ");
+    let message = Process::from_stat_verbose(&bad_guest_time, 1000, 1000, 100).unwrap_err().to_string();
+    assert!(message.lines().any(|l| l.contains("guest_time") && l.contains("\"x\"")));
+}
+
+#[test]
+fn process_argv0() {
+    let mut child = Command::new("sleep").arg0("-renamed sleep").arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    // The command line can briefly be empty while exec is finishing
+    let mut argv0 = None;
+    for _ in 0..100 {
+        argv0 = process.argv0().unwrap();
+        if argv0.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(argv0, Some("-renamed sleep".to_string()));
+    assert!(process.exe().unwrap().unwrap().ends_with("sleep"));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_oom_adj() {
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    // Raising the adjustment never needs a privilege
+    process.set_oom_score_adj(500).unwrap();
+    assert_eq!(process.oom_score_adj().unwrap(), 500);
+    assert!(process.oom_adj().unwrap() > 0);
+
+    process.set_oom_adj(15).unwrap();
+    assert_eq!(process.oom_adj().unwrap(), 15);
+    assert_eq!(process.oom_score_adj().unwrap(), 1000);
+
+    assert_eq!(process.set_oom_adj(16).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(process.set_oom_score_adj(-1001).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_is_thread_group_leader() {
+    let process = get_process();
+    assert!(process.is_thread_group_leader());
+
+    with_named_thread("psutil-leader", |tid| {
+        let from_tid = Process::new(tid).unwrap();
+        assert!(!from_tid.is_thread_group_leader());
+        assert_eq!(from_tid.tgid().unwrap(), process.pid);
+        assert_eq!(from_tid.thread_group_leader().unwrap(), process);
+    });
+}
+
+#[test]
+fn cpu_set() {
+    let mut set = CpuSet::new();
+    set.set(3);
+    set.set(200);
+    set.set(3);
+
+    assert!(set.contains(3) && set.contains(200) && !set.contains(4));
+    assert_eq!(set.count(), 2);
+    assert_eq!(set.iter().collect::<Vec<usize>>(), vec![3, 200]);
+
+    set.clear(200);
+    assert_eq!(set, CpuSet::from(&[3][..]));
+
+    let other = CpuSet::from(&[1, 3][..]);
+    assert_eq!(set.union(&other).iter().collect::<Vec<usize>>(), vec![1, 3]);
+    assert_eq!(set.intersection(&other), set);
+    assert!(CpuSet::from(&[1][..]).intersection(&set).is_empty());
+}
+
+#[test]
+fn process_cpu_affinity_mask() {
+    let process = get_process();
+    let cpus = process.cpu_affinity().unwrap();
+
+    assert!(cpus.count() >= 1);
+    assert_eq!(cpus.iter().collect::<Vec<usize>>(), process.get_cpu_affinity().unwrap());
+    process.set_cpu_affinity_mask(&cpus).unwrap();
+    assert_eq!(process.set_cpu_affinity_mask(&CpuSet::new()).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn process_reread() {
+    let mut process = get_process();
+    let first = process.reread().unwrap();
+    assert_eq!(first, process);
+    assert!(first.cpu_times().total() >= process.cpu_times().total());
+
+    process.starttime += 1;
+    assert_eq!(process.reread().unwrap_err().kind(), ErrorKind::NotFound);
+    process.set_identity_check(false);
+    assert_eq!(process.reread().unwrap(), first);
+}
+
+#[test]
+fn process_top_by() {
+    let top = psutil::process::top_by(3, |p| p.rss).unwrap();
+    assert!(top.len() <= 3);
+    assert!(top.windows(2).all(|w| w[0].rss >= w[1].rss));
+
+    // Reverse ranks the smallest keys first
+    let lowest = psutil::process::top_by(2, |p| std::cmp::Reverse(p.pid)).unwrap();
+    assert_eq!(lowest[0].pid, 1);
+    assert!(psutil::process::top_by(0, |p| p.pid).unwrap().is_empty());
+}
+
+#[test]
+fn process_collect_lenient() {
+    let lenient = Process::collect_lenient(getpid()).unwrap();
+    assert_eq!(lenient.process.pid, getpid());
+    assert_eq!(lenient.status.unwrap().pid, getpid());
+    assert!(lenient.memory.unwrap().resident > 0);
+    assert!(lenient.cmdline.unwrap().is_some());
+
+    let missing = Process::collect_lenient(psutil::PID::max_value()).unwrap_err();
+    assert_eq!(missing.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn process_username_groupname() {
+    let mut process = get_process();
+    process.uid = 0;
+    process.gid = 0;
+    assert_eq!(process.username().unwrap(), "root");
+    assert_eq!(process.groupname().unwrap(), "root");
+
+    // IDs without an entry fall back to the number
+    process.uid = 3999999;
+    process.gid = 3999999;
+    assert_eq!(process.username().unwrap(), "3999999");
+    assert_eq!(process.groupname().unwrap(), "3999999");
+}
+
+#[test]
+fn process_all_results() {
+    let results = psutil::process::all_results().unwrap();
+    let own = results.iter().find(|&&(pid, _)| pid == getpid()).unwrap();
+
+    assert_eq!(own.1.as_ref().unwrap().pid, getpid());
+    assert!(results.iter().all(|&(pid, ref result)| match *result {
+        Ok(ref process) => process.pid == pid,
+        Err(ref e) => e.kind() == ErrorKind::NotFound
+    }));
+}
+
+#[test]
+fn process_matches_name() {
+    let tempdir = TempDir::new("psutil-tests").unwrap();
+    let long = tempdir.path().join("psutil-long-program-name");
+    fs::copy("/bin/sleep", &long).unwrap();
+
+    let mut child = Command::new(&long).arg("10").spawn().unwrap();
+    let process = wait_for_exec(&child, "psutil-long-pro");
+
+    assert!(process.matches_name("psutil-long-program-name").unwrap());
+    assert!(process.matches_name("psutil-long-pro").unwrap());
+    assert!(!process.matches_name("psutil-long-program-other").unwrap());
+    assert!(!process.matches_name("sleep").unwrap());
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn parse_cmdline_setproctitle() {
+    // The title fills the 13 byte argument area, so the kernel carries on
+    // into the environment until it finds a NUL byte
+    let stat = STAT.replace(" 0 0 0 0 0 0\n", " 0 1000 1013 1013 1100 0\n");
+    let process = Process::from_stat(&stat, 1000, 1000, 100).unwrap();
+    let args = process.args_from_cmdline(b"nginx: masterPATH=/bin\0");
+    assert_eq!(args, Some(vec!["nginx: master".to_string()]));
+
+    // Without the argument area from stat the contents are used as they are
+    let process = Process::from_stat(STAT, 1000, 1000, 100).unwrap();
+    assert_eq!(process.args_from_cmdline(b"sleep\x0010\0").unwrap(), vec!["sleep", "10"]);
+}
+
+#[test]
+fn parse_cmdline_title() {
+    let process = Process::from_stat(STAT, 1000, 1000, 100).unwrap();
+    let title = process.args_from_cmdline(b"postgres: checkpointer   ").unwrap();
+    assert_eq!(title, vec!["postgres: checkpointer   "]);
+
+    let padded = process.args_from_cmdline(b"postgres: walwriter\0\0\0").unwrap();
+    assert_eq!(padded, vec!["postgres: walwriter"]);
+}
+
+#[test]
+fn parse_memory_map() {
+    let map: MemoryMap = FromStr::from_str(
+        "00400000-00452000 r-xp 00001000 08:02 173521      /usr/bin/my program").unwrap();
+    assert_eq!((map.start, map.end, map.size()), (0x400000, 0x452000, 0x52000));
+    assert!(map.perms.read && !map.perms.write && map.perms.execute && !map.perms.shared);
+    assert_eq!((map.offset, map.major, map.minor, map.inode), (0x1000, 8, 2, 173521));
+    assert_eq!(map.path, Some(PathBuf::from("/usr/bin/my program")));
+
+    let anonymous: MemoryMap = FromStr::from_str("7f0000000000-7f0000001000 rw-s 00000000 00:00 0 ").unwrap();
+    assert!(anonymous.perms.shared);
+    assert_eq!(anonymous.path, None);
+}
+
+#[test]
+fn parse_smaps_entries() {
+    let smaps = "\
+00400000-00452000 r-xp 00000000 08:02 173521      /usr/bin/dbus-daemon
+Rss:                  64 kB
+Private_Clean:        16 kB
+AnonHugePages:         0 kB
+THPeligible:           0
+VmFlags: rd ex mr mw me dw
+7f0000000000-7f0000400000 rw-p 00000000 00:00 0 
+Rss:                4096 kB
+Private_Dirty:      4096 kB
+AnonHugePages:      2048 kB
+Locked:             4096 kB
+THPeligible:           1
+VmFlags: rd wr mr mw me lo ac hg
+";
+    let entries = SmapsEntry::from_smaps(smaps).unwrap();
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].map.path, Some(PathBuf::from("/usr/bin/dbus-daemon")));
+    assert_eq!((entries[0].memory.rss, entries[0].memory.uss), (64 * 1024, 16 * 1024));
+    assert_eq!(entries[0].thp_eligible, Some(false));
+    assert!(entries[0].vm_flags.contains("ex") && !entries[0].vm_flags.locked());
+
+    assert_eq!(entries[1].map.path, None);
+    assert_eq!(entries[1].anon_huge_pages, 2048 * 1024);
+    assert!(entries[1].vm_flags.locked() && entries[1].vm_flags.thp_advised());
+    assert!(!entries[1].vm_flags.thp_disabled() && !entries[1].vm_flags.hugetlb());
+    assert_eq!(entries[1].vm_flags.iter().count(), 8);
+
+    assert!(SmapsEntry::from_smaps("Rss: 4 kB\n").is_err());
+}
+
+#[test]
+fn process_smaps() {
+    let entries = get_process().smaps().unwrap();
+    assert!(entries.iter().any(|e| e.map.path == Some(PathBuf::from("[stack]"))));
+    assert!(entries.iter().any(|e| e.vm_flags.contains("rd") && e.memory.rss > 0));
+}
+
+#[test]
+fn parse_numa_map() {
+    let map: NumaMap = FromStr::from_str(
+        "7f8b9c000000 interleave:0-1 file=/usr/lib/libc.so.6 mapped=37 mapmax=4 N0=20 N1=17 kernelpagesize_kB=4").unwrap();
+    assert_eq!((map.address, &map.policy[..]), (0x7f8b9c000000, "interleave:0-1"));
+    assert_eq!(map.file, Some(PathBuf::from("/usr/lib/libc.so.6")));
+    assert_eq!(map.nodes, vec![(0, 20), (1, 17)]);
+    assert_eq!((map.pages(), map.pages_on(1), map.pages_on(2)), (37, 17, 0));
+    assert_eq!(map.kernel_page_size, Some(4096));
+    assert_eq!((map.values["mapped"], map.values["mapmax"]), (37, 4));
+
+    let heap: NumaMap = FromStr::from_str("559825412000 default heap anon=1 dirty=1 N0=1").unwrap();
+    assert_eq!((heap.file, heap.flags), (None, vec!["heap".to_string()]));
+    assert!(NumaMap::from_str("559825412000 default anon=lots").is_err());
+}
+
+#[test]
+fn process_numa_maps() {
+    match get_process().numa_maps() {
+        Ok(maps) => assert!(maps.iter().any(|m| m.flags.iter().any(|f| f == "stack"))),
+        Err(e) => assert_eq!(e.kind(), ErrorKind::Other)
+    }
+}
+
+#[test]
+fn process_memory_maps() {
+    let process = get_process();
+    let maps = process.memory_maps().unwrap();
+    assert!(maps.iter().any(|m| m.path == Some(PathBuf::from("[stack]"))));
+
+    let executable = process.memory_maps_matching(|perms| perms.execute).unwrap();
+    assert!(!executable.is_empty() && executable.iter().all(|m| m.perms.execute));
+    assert!(process.writable_executable_maps().unwrap().iter().all(|m| m.perms.write && m.perms.execute));
+}
+
+#[test]
+fn process_memory_addresses() {
+    let process = get_process();
+    assert!(process.startcode() > 0 && process.startcode() < process.endcode());
+    assert!(process.start_data() <= process.end_data());
+    assert!(process.end_data() <= process.start_brk());
+    assert!(process.arg_start() < process.arg_end());
+    assert!(process.arg_end() <= process.env_start());
+    assert!(process.env_start() < process.env_end());
+    assert!(process.startstack() > process.start_brk());
+}
+
+#[test]
+fn process_start_order() {
+    let before = SystemTime::now() - Duration::from_secs(5);
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+
+    let newest = psutil::process::newest().unwrap().unwrap();
+    assert!(newest.starttime >= process.starttime);
+
+    let started = psutil::process::started_after(before).unwrap();
+    assert!(started.iter().any(|p| p.pid == process.pid));
+    assert!(started.windows(2).all(|w| w[0].starttime <= w[1].starttime));
+    assert!(psutil::process::started_after(SystemTime::now() + Duration::from_secs(3600)).unwrap().is_empty());
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_all_basic() {
+    let processes = psutil::process::all_basic().unwrap();
+    let &(ref process, ref memory) = processes.iter().find(|&&(ref p, _)| p.pid == getpid()).unwrap();
+    assert_eq!(process.comm, get_process().comm);
+    assert!(memory.resident > 0 && memory.size >= memory.resident);
+}
+
+#[test]
+fn process_debug_full() {
+    let process = get_process();
+    let debug = process.debug_full();
+    assert!(debug.starts_with(&format!("Process {{\n    pid: {} (thread group leader),\n", process.pid)));
+    assert!(debug.contains(&format!("comm: {:?},", process.comm)));
+    assert!(debug.contains("    started: 20") && debug.contains(" UTC ("));
+
+    with_named_thread("psutil-debug", |tid| {
+        let debug = Process::new(tid).unwrap().debug_full();
+        assert!(debug.contains(&format!("pid: {} (thread of {}),", tid, process.pid)));
+    });
+}
+
+#[test]
+fn process_health() {
+    let process = get_process();
+    let health = process.health().unwrap();
+    assert_eq!(health.process, process);
+    assert!(health.cpu_percent >= 0.0 && health.rss > 0 && health.num_threads >= 1);
+
+    let fds = health.num_fds.unwrap();
+    assert!(fds >= 3);
+    if let Some(limit) = health.fd_limit {
+        assert_eq!(health.fd_usage, Some(fds as f64 / limit as f64));
+    }
+}
+
+#[test]
+fn process_num_fds() {
+    let mut child = Command::new("sleep").arg("30")
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().unwrap();
+    let process = wait_for_exec(&child, "sleep");
+    assert_eq!(process.num_fds().unwrap(), 3);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_stat_raw() {
+    let process = get_process();
+    let fields = process.stat_raw().unwrap();
+
+    assert_eq!(fields[0], process.pid.to_string());
+    assert_eq!(fields[1], process.comm);
+    assert_eq!(fields[4], process.pgrp.to_string());
+    assert!(fields.len() >= 44);
+}
+
+#[test]
+fn process_mounts() {
+    let mounts = get_process().mounts().unwrap();
+    assert!(mounts.iter().any(|m| m.mount_point == PathBuf::from("/proc")));
+}
+
+#[test]
+fn process_cgroup_memory() {
+    // The cgroup may not be visible from inside a container
+    match get_process().cgroup_memory() {
+        Ok(memory) => assert!(memory.current > 0),
+        Err(e) => assert_eq!(e.kind(), ErrorKind::Other)
+    }
+}
+
+#[test]
+fn process_cgroup_cpu_stat() {
+    match get_process().cgroup_cpu_stat() {
+        Ok(stat) => assert!(stat.nr_throttled <= stat.nr_periods),
+        Err(e) => assert_eq!(e.kind(), ErrorKind::Other)
+    }
+}
+
+#[test]
+fn parse_cgroup_cpu_stat() {
+    let stat: CgroupCpuStat = FromStr::from_str(concat!(
+        "usage_usec 535105124\n",
+        "user_usec 452921689\n",
+        "system_usec 82183435\n",
+        "nr_periods 120\n",
+        "nr_throttled 7\n",
+        "throttled_usec 91000\n")).unwrap();
+
+    assert_eq!(stat.usage_usec, 535105124);
+    assert_eq!(stat.nr_periods, 120);
+    assert_eq!(stat.nr_throttled, 7);
+    assert_eq!(stat.throttled_usec, 91000);
+
+    // Without the cpu controller only the usage fields are present
+    let stat: CgroupCpuStat = FromStr::from_str("usage_usec 10\nuser_usec 6\n").unwrap();
+    assert_eq!(stat.nr_throttled, 0);
+    assert!(CgroupCpuStat::from_str("nr_periods 0\n").is_err());
+}
+
+#[test]
+fn parse_stat_terminal_name() {
+    let terminal = |tty_nr: u32| {
+        let stat = STAT.replace(" 1234 1234 0 -1 ", &format!(" 1234 1234 {} -1 ", tty_nr));
+        Process::from_stat(&stat, 1000, 1000, 100).unwrap().terminal_name().unwrap()
+    };
+
+    assert_eq!(terminal(0), None);
+    assert_eq!(terminal(4 << 8 | 1), Some("tty1".to_string()));
+    assert_eq!(terminal(4 << 8 | 65), Some("ttyS1".to_string()));
+    assert_eq!(terminal(136 << 8 | 3), Some("pts/3".to_string()));
+    assert_eq!(terminal(137 << 8 | 2), Some("pts/258".to_string()));
+    assert_eq!(terminal(1 << 8 | 3), Some("null".to_string()));
+}
+
+#[test]
+fn parse_statm_with_page_size() {
+    let memory = Memory::from_statm("10 5 2 1 0 3 0\n", 4096).unwrap();
+    assert_eq!(memory.lib, 0);
+    assert_eq!(memory.dt, 0);
+    assert_eq!(memory.size, 40960);
+    assert_eq!(memory.resident, 20480);
+    assert_eq!(memory.data, 12288);
+}
+
+#[test]
+fn process_rlimit() {
+    let process = get_process();
+    let (soft, hard) = process.get_rlimit(RLimit::NumFiles).unwrap();
+    assert!(soft <= hard);
+
+    process.set_rlimit(RLimit::NumFiles, soft, hard).unwrap();
+    assert_eq!(process.get_rlimit(RLimit::NumFiles).unwrap(), (soft, hard));
+}
+
+#[test]
+fn parse_statm_data_underflow() {
+    let memory = Memory::from_statm("10 5 2 1 0 18446744073709551611 0\n", 4096).unwrap();
+    assert_eq!(memory.size, 40960);
+    assert_eq!(memory.data, 0);
+}
+
+#[test]
+fn parse_smaps() {
+    let memory = MemoryFull::from_smaps(concat!(
+        "00400000-00401000 r-xp 00000000 08:01 1234 /bin/test\n",
+        "Size:                  4 kB\n",
+        "Rss:                   4 kB\n",
+        "Pss:                   2 kB\n",
+        "Shared_Clean:          4 kB\n",
+        "Private_Dirty:         0 kB\n",
+        "VmFlags: rd ex mr mw me\n",
+        "7ffe0000-7fff0000 rw-p 00000000 00:00 0 [stack]\n",
+        "Rss:                   8 kB\n",
+        "Pss:                   8 kB\n",
+        "Private_Clean:         4 kB\n",
+        "Private_Dirty:         4 kB\n",
+        "Swap:                 12 kB\n")).unwrap();
+
+    assert_eq!(memory.rss, 12 * 1024);
+    assert_eq!(memory.pss, 10 * 1024);
+    assert_eq!(memory.uss, 8 * 1024);
+    assert_eq!(memory.shared_clean, 4 * 1024);
+    assert_eq!(memory.swap, 12 * 1024);
+}
+
+#[test]
+fn process_memory_full_rollup() {
+    let memory = get_process().memory_full_rollup().unwrap();
+    assert!(memory.rss > 0);
+    assert!(memory.uss <= memory.pss && memory.pss <= memory.rss);
+}
+
+#[test]
+fn parse_statm_truncated() {
+    let memory = Memory::from_statm("10 5 2\n", 4096).unwrap();
+    assert_eq!(memory.share, 8192);
+    assert_eq!(memory.text, 0);
+    assert_eq!(memory.data, 0);
+
+    assert_eq!(Memory::from_statm("10\n", 4096).unwrap_err().kind(), ErrorKind::Other);
+    assert_eq!(Memory::from_statm("10 x 2\n", 4096).unwrap_err().kind(), ErrorKind::Other);
+}
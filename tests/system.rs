@@ -1,6 +1,70 @@
+#![cfg(target_os = "linux")]
+
 extern crate psutil;
 
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::time::{Duration,SystemTime};
+
+use psutil::system::PressureResource;
+
 #[test]
 fn uptime() {
     assert!(psutil::system::uptime() > 0);
 }
+
+#[test]
+fn boot_time() {
+    assert!(psutil::system::boot_time().unwrap() < SystemTime::now());
+}
+
+#[test]
+fn cpu_times() {
+    let times = psutil::system::cpu_times().unwrap();
+    assert!(times.busy() > 0.0);
+    assert!(times.busy() <= times.total());
+}
+
+#[test]
+fn cpu_count() {
+    assert!(psutil::system::cpu_count() >= 1);
+}
+
+#[test]
+fn read_sysctl() {
+    let pid_max: u32 = psutil::system::read_sysctl("kernel.pid_max").unwrap().parse().unwrap();
+    assert!(pid_max > 0);
+    assert_eq!(psutil::system::read_sysctl("kernel/pid_max").unwrap(), pid_max.to_string());
+}
+
+#[test]
+fn sysctl_invalid_keys() {
+    for key in &["..", "kernel..pid_max", "kernel/../../etc/passwd", ""] {
+        let error = psutil::system::read_sysctl(key).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+    let error = psutil::system::write_sysctl("kernel/../x", "1").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn parse_pressure() {
+    let pressure = PressureResource::from_str(concat!(
+        "some avg10=4.65 avg60=4.53 avg300=3.98 total=61732723\n",
+        "full avg10=0.00 avg60=0.00 avg300=0.01 total=3863881\n")).unwrap();
+    assert_eq!(pressure.some.avg10, 4.65);
+    assert_eq!(pressure.some.total, Duration::from_micros(61732723));
+    assert_eq!(pressure.full.unwrap().avg300, 0.01);
+
+    let pressure = PressureResource::from_str("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n");
+    assert_eq!(pressure.unwrap().full, None);
+    assert!(PressureResource::from_str("").is_err());
+}
+
+#[test]
+fn pressure() {
+    match psutil::system::pressure() {
+        Ok(pressure) => assert!(pressure.cpu.some.avg10 >= 0.0),
+        Err(e) => assert_eq!(e.kind(), ErrorKind::Other)
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(target_os = "linux")]
+
+extern crate psutil;
+
+use std::cell::Cell;
+use std::io::ErrorKind;
+
+use psutil::{Error,ReadPolicy,read_policy,retry,set_read_retries};
+
+/// Count the attempts `retry` makes with a read that always fails with `kind`
+fn attempts(policy: ReadPolicy, kind: ErrorKind) -> (u32, ErrorKind) {
+    let count = Cell::new(0);
+    let result: psutil::Result<()> = retry(policy, || {
+        count.set(count.get() + 1);
+        Err(Error::new(kind, "injected failure"))
+    });
+    (count.get(), result.unwrap_err().kind())
+}
+
+#[test]
+fn retry_transient() {
+    let policy = ReadPolicy { max_retries: 3, retry_not_found: false };
+    assert_eq!(attempts(policy, ErrorKind::Interrupted), (4, ErrorKind::Interrupted));
+    assert_eq!(attempts(policy, ErrorKind::UnexpectedEof), (4, ErrorKind::UnexpectedEof));
+    assert_eq!(attempts(policy, ErrorKind::PermissionDenied), (1, ErrorKind::PermissionDenied));
+}
+
+#[test]
+fn retry_not_found() {
+    let policy = ReadPolicy { max_retries: 2, retry_not_found: false };
+    assert_eq!(attempts(policy, ErrorKind::NotFound), (1, ErrorKind::NotFound));
+
+    let policy = ReadPolicy { max_retries: 2, retry_not_found: true };
+    assert_eq!(attempts(policy, ErrorKind::NotFound), (3, ErrorKind::NotFound));
+}
+
+#[test]
+fn retry_until_success() {
+    let count = Cell::new(0);
+    let result = retry(ReadPolicy::default(), || {
+        count.set(count.get() + 1);
+        match count.get() {
+            1 => Err(Error::new(ErrorKind::UnexpectedEof, "partial read")),
+            n => Ok(n)
+        }
+    });
+    assert_eq!(result.unwrap(), 2);
+}
+
+#[test]
+fn read_retries_policy() {
+    assert_eq!(read_policy(), ReadPolicy::default());
+    set_read_retries(5, true);
+    assert_eq!(read_policy(), ReadPolicy { max_retries: 5, retry_not_found: true });
+    set_read_retries(2, false);
+}